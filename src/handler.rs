@@ -1,11 +1,17 @@
 //! STAR Randomness web service route implementation
 
-use axum::body::Bytes;
-use axum::extract::{Json, Path, State};
-use axum::http::StatusCode;
+use axum::body::{Body, Bytes};
+use axum::extract::{Json, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use base64::prelude::{Engine as _, BASE64_STANDARD as BASE64};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLockReadGuard;
+use sha2::Sha512;
+use tokio::sync::{mpsc, RwLockReadGuard};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, instrument};
 
 use crate::instance::OPRFInstance;
@@ -21,6 +27,11 @@ pub struct RandomnessRequest {
     points: Vec<String>,
     /// Optional request for evaluation within a specific epoch
     epoch: Option<u8>,
+    /// Request a batched DLEQ proof covering every point in this
+    /// request, so the client can verify the result was produced under
+    /// the key advertised by /info rather than trusting the server.
+    #[serde(default)]
+    prove: bool,
 }
 
 /// Response structure for the randomness endpoint
@@ -32,6 +43,10 @@ pub struct RandomnessResponse {
     points: Vec<String>,
     /// Randomness epoch used in the evaluation
     epoch: u8,
+    /// Base64-encoded, constant-size batched DLEQ proof covering every
+    /// point above, present only when the request set `prove: true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<String>,
 }
 
 /// Response structure for the info endpoint
@@ -69,12 +84,94 @@ struct ErrorResponse {
     message: String,
 }
 
+/// Media type clients opt into for the compact binary response encoding.
+/// JSON remains the default for existing clients that don't send this.
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Whether the client's `Accept` header asks for the binary encoding.
+fn wants_octet_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| accept.split(',').any(|kind| kind.trim().starts_with(OCTET_STREAM)))
+        .unwrap_or(false)
+}
+
+/// Implemented by response bodies that have a compact binary encoding,
+/// used when the client opts in via `Accept: application/octet-stream`.
+trait BinaryEncode {
+    fn encode_binary(&self) -> Vec<u8>;
+}
+
+/// Serialize `value` as JSON or as its compact binary encoding,
+/// depending on what the client's `Accept` header asked for.
+fn negotiate<T: Serialize + BinaryEncode>(headers: &HeaderMap, value: T) -> Response {
+    if wants_octet_stream(headers) {
+        (
+            [(header::CONTENT_TYPE, OCTET_STREAM)],
+            value.encode_binary(),
+        )
+            .into_response()
+    } else {
+        Json(value).into_response()
+    }
+}
+
+impl BinaryEncode for RandomnessResponse {
+    /// `epoch:u8` followed by each point's raw 32-byte compressed
+    /// Ristretto encoding, concatenated with no separators, then, when a
+    /// proof was requested, the raw bincode-serialized proof
+    /// length-prefixed with `u32 LE`.
+    fn encode_binary(&self) -> Vec<u8> {
+        let mut body = Vec::with_capacity(1 + self.points.len() * ppoprf::COMPRESSED_POINT_LEN);
+        body.push(self.epoch);
+        for base64_point in &self.points {
+            // Points were base64-encoded for the JSON path; decode back
+            // to raw bytes for the binary wire format.
+            let raw = BASE64
+                .decode(base64_point)
+                .expect("points are always produced by our own base64 encoding");
+            body.extend_from_slice(&raw);
+        }
+        if let Some(proof) = &self.proof {
+            let proof = BASE64
+                .decode(proof)
+                .expect("proof is always produced by our own base64 encoding");
+            body.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+            body.extend_from_slice(&proof);
+        }
+        body
+    }
+}
+
+impl BinaryEncode for InfoResponse {
+    /// `epoch:u8`, `max_points:u32 LE`, the raw bincode-serialized public
+    /// key length-prefixed with `u32 LE`, then the RFC 3339
+    /// `next_epoch_time` string length-prefixed with `u16 LE`.
+    fn encode_binary(&self) -> Vec<u8> {
+        let public_key = BASE64
+            .decode(&self.public_key)
+            .expect("public_key is always produced by our own base64 encoding");
+        let next_epoch_time = self.next_epoch_time.as_bytes();
+
+        let mut body = Vec::with_capacity(1 + 4 + 4 + public_key.len() + 2 + next_epoch_time.len());
+        body.push(self.current_epoch);
+        body.extend_from_slice(&(self.max_points as u32).to_le_bytes());
+        body.extend_from_slice(&(public_key.len() as u32).to_le_bytes());
+        body.extend_from_slice(&public_key);
+        body.extend_from_slice(&(next_epoch_time.len() as u16).to_le_bytes());
+        body.extend_from_slice(next_epoch_time);
+        body
+    }
+}
+
 impl axum::response::IntoResponse for Error {
     /// Construct an http response from our error type
     fn into_response(self) -> axum::response::Response {
         let code = match self {
             Error::InstanceNotFound(_) => StatusCode::NOT_FOUND,
-            Error::PPOPRFNotReady => StatusCode::SERVICE_UNAVAILABLE,
+            Error::EpochNotCommitted(_) => StatusCode::NOT_FOUND,
+            Error::PPOPRFNotReady | Error::ProofUnavailable => StatusCode::SERVICE_UNAVAILABLE,
             // Other cases are the client's fault.
             _ => StatusCode::BAD_REQUEST,
         };
@@ -85,6 +182,39 @@ impl axum::response::IntoResponse for Error {
     }
 }
 
+/// Media type clients opt into to receive `/randomness` results as a
+/// newline-delimited JSON stream instead of one buffered JSON array.
+const NDJSON: &str = "application/x-ndjson";
+
+/// Whether the client's `Accept` header asks for the streaming encoding.
+fn wants_ndjson_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(|accept| accept.split(',').any(|kind| kind.trim().starts_with(NDJSON)))
+        .unwrap_or(false)
+}
+
+/// One evaluated point, emitted as its own newline-delimited JSON line
+/// by the streaming randomness path.
+#[derive(Serialize, Debug)]
+struct RandomnessStreamLine {
+    /// Base64-encoded, compressed result point.
+    point: String,
+    /// Randomness epoch used in the evaluation.
+    epoch: u8,
+}
+
+/// Terminal line emitted in place of the next `RandomnessStreamLine` if
+/// evaluation fails partway through a batch. The response has already
+/// committed to `200 OK`, so this is the only way a client can tell a
+/// stream that ends early from one that evaluated every requested point.
+#[derive(Serialize, Debug)]
+struct RandomnessStreamError {
+    /// Human-readable reason the stream ended early.
+    error: String,
+}
+
 async fn get_server_from_state<'a>(
     state: &'a OPRFState,
     instance_name: &'a str,
@@ -97,67 +227,311 @@ async fn get_server_from_state<'a>(
         .await)
 }
 
+/// Evaluate a single base64-encoded point against `instance_name` at `epoch`.
+async fn evaluate_one(
+    state: &OPRFState,
+    instance_name: &str,
+    epoch: u8,
+    base64_point: String,
+) -> Result<String> {
+    let input = BASE64.decode(base64_point)?;
+    // FIXME: Point::from is fallible and needs to return a result.
+    // partial work-around: check correct length
+    if input.len() != ppoprf::COMPRESSED_POINT_LEN {
+        return Err(Error::BadPoint);
+    }
+    let state_guard = get_server_from_state(state, instance_name).await?;
+    let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+
+    if let Some(cache) = &instance.eval_cache {
+        if let Some(cached) = cache.get(&(epoch, input.clone())) {
+            return Ok(cached);
+        }
+    }
+
+    let point = ppoprf::Point::from(input.as_slice());
+    let evaluation = instance.server.eval(&point, epoch, false)?;
+    let output = BASE64.encode(evaluation.output.as_bytes());
+
+    if let Some(cache) = &instance.eval_cache {
+        cache.insert((epoch, input), output.clone());
+    }
+
+    Ok(output)
+}
+
+/// Evaluate a batch of points incrementally, streaming each result to the
+/// client as its own newline-delimited JSON line as soon as it is computed,
+/// rather than buffering the whole batch in memory before replying.
+async fn randomness_stream(
+    state: OPRFState,
+    instance_name: String,
+    epoch: u8,
+    points: Vec<String>,
+) -> Response {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        for base64_point in points {
+            let mut frame = match evaluate_one(&state, &instance_name, epoch, base64_point).await {
+                Ok(point) => serde_json::to_vec(&RandomnessStreamLine { point, epoch })
+                    .expect("RandomnessStreamLine always serializes to json"),
+                Err(error) => {
+                    let mut frame = serde_json::to_vec(&RandomnessStreamError {
+                        error: error.to_string(),
+                    })
+                    .expect("RandomnessStreamError always serializes to json");
+                    frame.push(b'\n');
+                    // A client reading line-by-line sees this in place of
+                    // the next point, so it can tell the batch was cut
+                    // short instead of mistaking it for a complete result.
+                    let _ = tx.send(Ok::<_, std::convert::Infallible>(Bytes::from(frame)));
+                    break;
+                }
+            };
+            frame.push(b'\n');
+            if tx.send(Ok::<_, std::convert::Infallible>(Bytes::from(frame))).is_err() {
+                // Client went away; stop evaluating the rest of the batch.
+                break;
+            }
+        }
+    });
+
+    let body = Body::from_stream(UnboundedReceiverStream::new(rx));
+    Response::builder()
+        .header(header::CONTENT_TYPE, NDJSON)
+        .body(body)
+        .expect("response with a streaming body should always build")
+}
+
+/// Fiat-Shamir weight `c_i` for the `index`-th point in a batched DLEQ
+/// proof, binding the point, its evaluation, and the epoch so a proof
+/// can never be replayed against a different set of points.
+fn batch_challenge_weight(index: usize, input: &[u8], output: &[u8], epoch: u8) -> Scalar {
+    let mut transcript = Vec::with_capacity(8 + input.len() + output.len() + 1);
+    transcript.extend_from_slice(&(index as u64).to_le_bytes());
+    transcript.extend_from_slice(input);
+    transcript.extend_from_slice(output);
+    transcript.push(epoch);
+    Scalar::hash_from_bytes::<Sha512>(&transcript)
+}
+
+/// Build a single constant-size batched Chaum-Pedersen DLEQ proof
+/// covering every point evaluated in a `/randomness` request, so a
+/// client can verify every returned point was produced under the key
+/// advertised at `/info` without trusting the server.
+///
+/// Folds the request's `(P_i, Q_i)` pairs into one composite point
+/// `M = Σ c_i·P_i` via Fiat-Shamir weights `c_i = H(i ‖ P_i ‖ Q_i ‖
+/// epoch)`, then asks the current epoch's key to prove itself against
+/// that single point. This never needs the raw per-epoch scalar `k` in
+/// this process: `M` is just another point as far as `k·M` is
+/// concerned, and `ppoprf::Server::eval`'s `verifiable` mode already
+/// knows how to produce a DLEQ proof of `k·M` against the published
+/// public key using the `k` it holds internally.
+async fn build_batch_proof(
+    state: &OPRFState,
+    instance_name: &str,
+    epoch: u8,
+    inputs: &[String],
+    outputs: &[String],
+) -> Result<String> {
+    let mut composite = RistrettoPoint::identity();
+    for (index, (input_b64, output_b64)) in inputs.iter().zip(outputs).enumerate() {
+        let input_bytes = BASE64.decode(input_b64)?;
+        let output_bytes = BASE64.decode(output_b64)?;
+        let input_point = CompressedRistretto::from_slice(&input_bytes)
+            .decompress()
+            .ok_or(Error::BadPoint)?;
+        let weight = batch_challenge_weight(index, &input_bytes, &output_bytes, epoch);
+        composite += input_point * weight;
+    }
+
+    let state_guard = get_server_from_state(state, instance_name).await?;
+    let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+    let composite_point = ppoprf::Point::from(composite.compress().as_bytes().as_slice());
+    // A punctured or otherwise historical epoch can no longer be proved
+    // against; that surfaces here as an error rather than a fabricated
+    // or silently-omitted proof.
+    let evaluation = instance.server.eval(&composite_point, epoch, true)?;
+    let proof = evaluation.proof.ok_or(Error::ProofUnavailable)?;
+    let proof_bytes = proof.serialize_to_bincode()?;
+    Ok(BASE64.encode(proof_bytes))
+}
+
 /// Process PPOPRF evaluation requests
 #[instrument(skip(state, request))]
 async fn randomness(
     state: OPRFState,
     instance_name: String,
     request: RandomnessRequest,
-) -> Result<Json<RandomnessResponse>> {
+    headers: HeaderMap,
+) -> Result<Response> {
     debug!("recv: {request:?}");
-    let state_guard = get_server_from_state(&state, &instance_name).await?;
-    match state_guard.as_ref() {
-        None => Err(Error::PPOPRFNotReady),
-        Some(state) => {
-            let epoch = request.epoch.unwrap_or(state.epoch);
-            if epoch != state.epoch {
-                return Err(Error::BadEpoch(epoch));
-            }
-            if request.points.len() > crate::MAX_POINTS {
-                return Err(Error::TooManyPoints);
-            }
-            // Don't support returning proofs until we have a more
-            // space-efficient batch proof implemented in ppoprf.
-            let mut points = Vec::with_capacity(request.points.len());
-            for base64_point in request.points {
-                let input = BASE64.decode(base64_point)?;
-                // FIXME: Point::from is fallible and needs to return a result.
-                // partial work-around: check correct length
-                if input.len() != ppoprf::COMPRESSED_POINT_LEN {
-                    return Err(Error::BadPoint);
-                }
-                let point = ppoprf::Point::from(input.as_slice());
-                let evaluation = state.server.eval(&point, epoch, false)?;
-                points.push(BASE64.encode(evaluation.output.as_bytes()));
-            }
-            let response = RandomnessResponse { points, epoch };
-            debug!("send: {response:?}");
-            Ok(Json(response))
+    let epoch = {
+        let state_guard = get_server_from_state(&state, &instance_name).await?;
+        let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+        let epoch = request.epoch.unwrap_or(instance.epoch);
+        if epoch != instance.epoch {
+            return Err(Error::BadEpoch(epoch));
+        }
+        epoch
+    };
+    if request.points.len() > crate::MAX_POINTS {
+        return Err(Error::TooManyPoints);
+    }
+
+    // Large batches can opt into the streaming path via the Accept header
+    // to bound peak memory and start returning data immediately; a
+    // constant-size proof covering the whole batch isn't meaningful to
+    // attach to a stream of incremental frames, so `prove` is only
+    // honored on the buffered path below.
+    if wants_ndjson_stream(&headers) {
+        if request.prove {
+            return Err(Error::ProofUnavailable);
         }
+        return Ok(randomness_stream(state, instance_name, epoch, request.points).await);
+    }
+
+    let mut points = Vec::with_capacity(request.points.len());
+    for base64_point in &request.points {
+        points.push(evaluate_one(&state, &instance_name, epoch, base64_point.clone()).await?);
     }
+    let proof = if request.prove {
+        Some(build_batch_proof(&state, &instance_name, epoch, &request.points, &points).await?)
+    } else {
+        None
+    };
+    let response = RandomnessResponse {
+        points,
+        epoch,
+        proof,
+    };
+    debug!("send: {response:?}");
+    Ok(negotiate(&headers, response))
 }
 
 /// Process PPOPRF evaluation requests using default instance
 pub async fn default_instance_randomness(
     State(state): State<OPRFState>,
+    headers: HeaderMap,
     Json(request): Json<RandomnessRequest>,
-) -> Result<Json<RandomnessResponse>> {
+) -> Result<Response> {
     let instance_name = state.default_instance.clone();
-    randomness(state, instance_name, request).await
+    randomness(state, instance_name, request, headers).await
 }
 
 /// Process PPOPRF evaluation requests using specific instance
 pub async fn specific_instance_randomness(
     State(state): State<OPRFState>,
     Path(instance_name): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<RandomnessRequest>,
-) -> Result<Json<RandomnessResponse>> {
-    randomness(state, instance_name, request).await
+) -> Result<Response> {
+    randomness(state, instance_name, request, headers).await
+}
+
+/// One sub-request within a `/batch` call: evaluate `points` against
+/// `instance` at `epoch` (or that instance's current epoch, if omitted),
+/// same as a single `/randomness` request.
+#[derive(Deserialize, Debug)]
+pub struct BatchEntryRequest {
+    /// Instance to evaluate against.
+    instance: String,
+    /// Optional request for evaluation within a specific epoch.
+    epoch: Option<u8>,
+    /// Points to evaluate, base64-encoded compressed Ristretto points.
+    points: Vec<String>,
+}
+
+/// Request structure for the batch endpoint
+#[derive(Deserialize, Debug)]
+pub struct BatchRequest {
+    /// Independent sub-requests, each evaluated against its own instance
+    /// and epoch. `MAX_POINTS` is enforced once across every entry's
+    /// points combined, rather than per entry, so a batch can't be used
+    /// to exceed the usual single-request evaluation budget.
+    entries: Vec<BatchEntryRequest>,
+}
+
+/// Result of one `/batch` entry: either its evaluated points, or the
+/// error that entry hit, tagged with the instance name so results can
+/// be matched back up against the request that produced them. A failing
+/// entry never fails the rest of the batch.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum BatchEntryResponse {
+    Ok {
+        instance: String,
+        epoch: u8,
+        points: Vec<String>,
+    },
+    Err {
+        instance: String,
+        message: String,
+    },
+}
+
+/// Evaluate one `/batch` entry against its own instance and epoch,
+/// reusing the same validation and evaluation path as `/randomness`.
+async fn evaluate_batch_entry(state: &OPRFState, entry: &BatchEntryRequest) -> Result<(u8, Vec<String>)> {
+    let epoch = {
+        let state_guard = get_server_from_state(state, &entry.instance).await?;
+        let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+        let epoch = entry.epoch.unwrap_or(instance.epoch);
+        if epoch != instance.epoch {
+            return Err(Error::BadEpoch(epoch));
+        }
+        epoch
+    };
+    let mut points = Vec::with_capacity(entry.points.len());
+    for base64_point in &entry.points {
+        points.push(evaluate_one(state, &entry.instance, epoch, base64_point.clone()).await?);
+    }
+    Ok((epoch, points))
+}
+
+/// Process a `/batch` request: evaluate every entry against its own
+/// instance and epoch, reporting each entry's outcome independently so
+/// one instance being unavailable or given a stale epoch doesn't fail
+/// the other entries in the same call.
+#[instrument(skip(state, request))]
+async fn batch(state: OPRFState, request: BatchRequest) -> Result<Json<Vec<BatchEntryResponse>>> {
+    debug!("recv: {request:?}");
+    let total_points: usize = request.entries.iter().map(|entry| entry.points.len()).sum();
+    if total_points > crate::MAX_POINTS {
+        return Err(Error::TooManyPoints);
+    }
+
+    let mut results = Vec::with_capacity(request.entries.len());
+    for entry in request.entries {
+        let result = match evaluate_batch_entry(&state, &entry).await {
+            Ok((epoch, points)) => BatchEntryResponse::Ok {
+                instance: entry.instance,
+                epoch,
+                points,
+            },
+            Err(error) => BatchEntryResponse::Err {
+                instance: entry.instance,
+                message: error.to_string(),
+            },
+        };
+        results.push(result);
+    }
+    debug!("send: {results:?}");
+    Ok(Json(results))
+}
+
+/// Process a `/batch` request carrying many independent evaluations.
+pub async fn batch_randomness(
+    State(state): State<OPRFState>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<Vec<BatchEntryResponse>>> {
+    batch(state, request).await
 }
 
 /// Provide PPOPRF epoch and key metadata
 #[instrument(skip(state))]
-async fn info(state: OPRFState, instance_name: String) -> Result<Json<InfoResponse>> {
+async fn info(state: OPRFState, instance_name: String, headers: HeaderMap) -> Result<Response> {
     debug!("recv: info request");
     let state_guard = get_server_from_state(&state, &instance_name).await?;
     match state_guard.as_ref() {
@@ -172,23 +546,27 @@ async fn info(state: OPRFState, instance_name: String) -> Result<Json<InfoRespon
                 public_key,
             };
             debug!("send: {response:?}");
-            Ok(Json(response))
+            Ok(negotiate(&headers, response))
         }
     }
 }
 
 /// Provide PPOPRF epoch and key metadata using default instance
-pub async fn default_instance_info(State(state): State<OPRFState>) -> Result<Json<InfoResponse>> {
+pub async fn default_instance_info(
+    State(state): State<OPRFState>,
+    headers: HeaderMap,
+) -> Result<Response> {
     let instance_name = state.default_instance.clone();
-    info(state, instance_name).await
+    info(state, instance_name, headers).await
 }
 
 /// Provide PPOPRF epoch and key metadata using specific instance
 pub async fn specific_instance_info(
     State(state): State<OPRFState>,
     Path(instance_name): Path<String>,
-) -> Result<Json<InfoResponse>> {
-    info(state, instance_name).await
+    headers: HeaderMap,
+) -> Result<Response> {
+    info(state, instance_name, headers).await
 }
 
 // Lists all available instances, as well as the default instance
@@ -199,6 +577,91 @@ pub async fn list_instances(State(state): State<OPRFState>) -> Result<Json<ListI
     }))
 }
 
+/// Liveness probe. Always reports success once the web server is up to
+/// accept connections; unlike `/status`, it does no work and never
+/// depends on key-sync state, so orchestrators can poll it cheaply and
+/// frequently.
+pub async fn health() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Readiness for a single instance, as reported by `/status`.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    /// Whether this instance has PPOPRF keys and can serve `/randomness`.
+    ready: bool,
+    /// Currently active randomness epoch, absent while awaiting key sync.
+    epoch: Option<u8>,
+    /// Timestamp of the next epoch rotation, absent while awaiting key sync.
+    next_epoch_time: Option<String>,
+}
+
+/// Response for the `/status` readiness endpoint.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusResponse {
+    /// Whether every instance is ready. Mirrors the response status
+    /// code: 200 when true, 503 otherwise.
+    ready: bool,
+    /// Per-instance readiness, keyed by instance name.
+    instances: std::collections::HashMap<String, InstanceStatus>,
+    /// Whether the leader's most recent push of key material to
+    /// nitriding succeeded. Absent when this node has never needed to
+    /// push (a worker, or a leader that hasn't synced a key yet).
+    key_sync_healthy: Option<bool>,
+}
+
+/// Readiness probe. Reports whether every configured instance has
+/// PPOPRF keys in place (set directly at startup, or synced from the
+/// leader via `set_ppoprf_private_key` when enclave key sync is
+/// enabled), returning 503 until all of them do so orchestrators can
+/// hold traffic off a worker enclave still waiting on key sync. Also
+/// folds in the leader's nitriding push health, so a readiness probe
+/// catches a leader that's ready but has started failing to propagate
+/// new keys to its workers.
+pub async fn status(State(state): State<OPRFState>) -> (StatusCode, Json<StatusResponse>) {
+    let mut instances = std::collections::HashMap::with_capacity(state.instances.len());
+    // Checked via the `OnceCell`'s synchronous `get()` rather than the
+    // lazily-spawning async accessor, so a readiness probe never has the
+    // side effect of starting the push supervisor on a node that has
+    // never needed one.
+    let key_sync_healthy = state.nitriding_push.get().map(|push| push.is_healthy());
+    let mut ready = key_sync_healthy.unwrap_or(true);
+    for (instance_name, instance) in &state.instances {
+        let instance_guard = instance.read().await;
+        let status = match instance_guard.as_ref() {
+            Some(instance) => InstanceStatus {
+                ready: true,
+                epoch: Some(instance.epoch),
+                next_epoch_time: Some(instance.next_epoch_time.clone()),
+            },
+            None => {
+                ready = false;
+                InstanceStatus {
+                    ready: false,
+                    epoch: None,
+                    next_epoch_time: None,
+                }
+            }
+        };
+        instances.insert(instance_name.clone(), status);
+    }
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        code,
+        Json(StatusResponse {
+            ready,
+            instances,
+            key_sync_healthy,
+        }),
+    )
+}
+
 /// Stores keys sent by nitriding, and sourced from the leader enclave.
 pub async fn set_ppoprf_private_key(State(state): State<OPRFState>, body: Bytes) -> Result<()> {
     state.set_private_keys(body).await
@@ -209,3 +672,138 @@ pub async fn get_ppoprf_private_key(State(state): State<OPRFState>) -> Result<Ve
     state.create_missing_instances().await;
     state.get_private_keys().await
 }
+
+/// Responder side of the enclave key-sync secure channel handshake.
+/// Relayed by nitriding the same way as `/enclave/state`.
+pub async fn enclave_handshake(State(state): State<OPRFState>, body: Bytes) -> Result<Vec<u8>> {
+    state.handle_handshake(&body).await
+}
+
+/// Response for the transparency-log root endpoint.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencyRootResponse {
+    /// Base64-encoded current Merkle root.
+    root: String,
+    /// Number of epochs committed to the log so far.
+    leaf_count: usize,
+}
+
+/// Query parameters identifying which committed leaf to prove inclusion
+/// for. The public key is required alongside the epoch because the
+/// epoch tag wraps and gets reused across key rotations, so `epoch`
+/// alone doesn't uniquely identify a leaf once more than one key has
+/// been in effect.
+#[derive(Deserialize, Debug)]
+pub struct TransparencyProofQuery {
+    epoch: u8,
+    /// Base64-encoded public key the client evaluated against.
+    public_key: String,
+}
+
+/// One sibling hash on an inclusion proof's path from leaf to root.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofStepResponse {
+    /// Base64-encoded sibling hash at this level.
+    sibling: String,
+    /// Whether the accumulated hash was the left operand when combined
+    /// with this sibling.
+    leaf_is_left: bool,
+}
+
+/// Response for the transparency-log inclusion-proof endpoint.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TransparencyProofResponse {
+    /// Index of the queried leaf in the log.
+    leaf_index: usize,
+    /// Base64-encoded current Merkle root the proof resolves against.
+    root: String,
+    /// Sibling hashes from the leaf's level up to the root.
+    steps: Vec<ProofStepResponse>,
+}
+
+/// Fetch the current transparency-log root and leaf count for an instance.
+#[instrument(skip(state))]
+async fn transparency_root(
+    state: OPRFState,
+    instance_name: String,
+) -> Result<Json<TransparencyRootResponse>> {
+    let state_guard = get_server_from_state(&state, &instance_name).await?;
+    let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+    let root = instance
+        .transparency_log
+        .root()
+        .ok_or(Error::EpochNotCommitted(instance.epoch))?;
+    Ok(Json(TransparencyRootResponse {
+        root: BASE64.encode(root),
+        leaf_count: instance.transparency_log.len(),
+    }))
+}
+
+/// Fetch the transparency-log root using the default instance.
+pub async fn default_instance_transparency_root(
+    State(state): State<OPRFState>,
+) -> Result<Json<TransparencyRootResponse>> {
+    let instance_name = state.default_instance.clone();
+    transparency_root(state, instance_name).await
+}
+
+/// Fetch the transparency-log root using a specific instance.
+pub async fn specific_instance_transparency_root(
+    State(state): State<OPRFState>,
+    Path(instance_name): Path<String>,
+) -> Result<Json<TransparencyRootResponse>> {
+    transparency_root(state, instance_name).await
+}
+
+/// Fetch an inclusion proof for the queried `(epoch, public_key)` leaf.
+#[instrument(skip(state))]
+async fn transparency_proof(
+    state: OPRFState,
+    instance_name: String,
+    query: TransparencyProofQuery,
+) -> Result<Json<TransparencyProofResponse>> {
+    let public_key = BASE64.decode(query.public_key)?;
+    let state_guard = get_server_from_state(&state, &instance_name).await?;
+    let instance = state_guard.as_ref().ok_or(Error::PPOPRFNotReady)?;
+    let proof = instance
+        .transparency_log
+        .prove(query.epoch, &public_key)
+        .ok_or(Error::EpochNotCommitted(query.epoch))?;
+    let root = instance
+        .transparency_log
+        .root()
+        .ok_or(Error::EpochNotCommitted(query.epoch))?;
+    Ok(Json(TransparencyProofResponse {
+        leaf_index: proof.leaf_index,
+        root: BASE64.encode(root),
+        steps: proof
+            .steps
+            .into_iter()
+            .map(|step| ProofStepResponse {
+                sibling: BASE64.encode(step.sibling),
+                leaf_is_left: step.leaf_is_left,
+            })
+            .collect(),
+    }))
+}
+
+/// Fetch an inclusion proof using the default instance.
+pub async fn default_instance_transparency_proof(
+    State(state): State<OPRFState>,
+    Query(query): Query<TransparencyProofQuery>,
+) -> Result<Json<TransparencyProofResponse>> {
+    let instance_name = state.default_instance.clone();
+    transparency_proof(state, instance_name, query).await
+}
+
+/// Fetch an inclusion proof using a specific instance.
+pub async fn specific_instance_transparency_proof(
+    State(state): State<OPRFState>,
+    Path(instance_name): Path<String>,
+    Query(query): Query<TransparencyProofQuery>,
+) -> Result<Json<TransparencyProofResponse>> {
+    transparency_proof(state, instance_name, query).await
+}
@@ -3,9 +3,19 @@ use tokio::task::JoinHandle;
 use tracing::info;
 
 use crate::result::Result;
+use crate::transparency::TransparencyLog;
 use crate::{util::format_rfc3339, Config};
 use ppoprf::ppoprf;
 
+/// Cache of recent `(epoch, compressed_point_bytes)` evaluations for an
+/// instance, to skip redundant scalar multiplications for repeated
+/// client nonces within the same epoch. Entries are keyed on epoch so a
+/// rotation or puncture can never be confused with a still-valid entry;
+/// [`OPRFServer::epoch_loop`](crate::state::OPRFServer) also clears the
+/// cache outright whenever the epoch advances, since the old epoch's
+/// entries can never be served again regardless.
+pub type EvalCache = quick_cache::sync::Cache<(u8, Vec<u8>), String>;
+
 /// Internal state of an OPRF instance
 pub struct OPRFInstance {
     /// oprf implementation
@@ -18,6 +28,19 @@ pub struct OPRFInstance {
     pub next_epoch_time: String,
     /// Handle for the background task associated with the instance
     pub background_task_handle: Option<JoinHandle<()>>,
+    /// Append-only log committing this instance's public key at every
+    /// epoch it has held, so clients can audit that the key they
+    /// evaluated against was actually published rather than swapped in
+    /// just for them.
+    pub transparency_log: TransparencyLog,
+    /// Bounded evaluation cache, or `None` if `--eval-cache-size` is 0.
+    pub eval_cache: Option<EvalCache>,
+}
+
+/// Serialize `server`'s current public key for appending to a
+/// [`TransparencyLog`] leaf.
+pub fn public_key_bytes(server: &ppoprf::Server) -> Result<Vec<u8>> {
+    Ok(server.get_public_key().serialize_to_bincode()?)
 }
 
 impl OPRFInstance {
@@ -77,12 +100,20 @@ impl OPRFInstance {
             }
         }
 
+        let mut transparency_log = TransparencyLog::new();
+        transparency_log.append(current_epoch, &public_key_bytes(&server)?);
+
+        let eval_cache = (config.eval_cache_size > 0)
+            .then(|| quick_cache::sync::Cache::new(config.eval_cache_size));
+
         Ok(OPRFInstance {
             server,
             epoch: current_epoch,
             epoch_duration,
             next_epoch_time: format_rfc3339(&next_epoch_time),
             background_task_handle: None,
+            transparency_log,
+            eval_cache,
         })
     }
 }
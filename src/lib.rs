@@ -95,6 +95,66 @@ pub unsafe extern "C" fn randomness_server_eval(
     false
 }
 
+/// Evaluate the PPOPRF for `count` consecutive compressed Ristretto
+/// points packed into `input`, writing each result contiguously into
+/// `output`. Evaluating a whole batch under one call amortizes the FFI
+/// and per-call overhead of randomness_server_eval() for embedders that
+/// request many points at once.
+///
+/// Returns the number of points that were evaluated successfully. A
+/// point that fails to parse or evaluate has its output slot zeroed so
+/// callers checking only the returned count never read stale bytes for
+/// it.
+///
+/// # Safety
+///
+/// The `ptr` argument must point to a valid RandomnessServer state
+/// struct, such as is returned by randomness_server_create().
+///
+/// The `input` argument must point to `count * COMPRESSED_POINT_LEN`
+/// accessible bytes, and `output` to `count * COMPRESSED_POINT_LEN`
+/// accessible and writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn randomness_server_eval_batch(
+    ptr: *const RandomnessServer,
+    input: *const u8,
+    count: usize,
+    md: u8,
+    verifiable: bool,
+    output: *mut u8,
+) -> usize {
+    // Verify arguments.
+    assert!(!ptr.is_null());
+    assert!(!input.is_null());
+    assert!(!output.is_null());
+
+    // Convert our *const argument to a &ppoprf::Server without taking ownership.
+    let server = &(*ptr).inner;
+
+    let mut successes = 0;
+    for i in 0..count {
+        let point_input = std::slice::from_raw_parts(
+            input.add(i * ppoprf::COMPRESSED_POINT_LEN),
+            ppoprf::COMPRESSED_POINT_LEN,
+        );
+        let point_output = output.add(i * ppoprf::COMPRESSED_POINT_LEN);
+        let point = ppoprf::Point::from(point_input);
+        let evaluated = server.eval(&point, md, verifiable).ok();
+        match evaluated {
+            Some(result) => {
+                std::ptr::copy_nonoverlapping(
+                    result.output.as_bytes().as_ptr(),
+                    point_output,
+                    ppoprf::COMPRESSED_POINT_LEN,
+                );
+                successes += 1;
+            }
+            None => std::ptr::write_bytes(point_output, 0, ppoprf::COMPRESSED_POINT_LEN),
+        }
+    }
+    successes
+}
+
 /// Puncture the given md value from the PPOPRF.
 ///
 /// # Safety
@@ -174,6 +234,33 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Batch evaluation of multiple points in one call.
+    fn batch_eval() {
+        let server = randomness_server_create();
+        assert!(!server.is_null());
+
+        const COUNT: usize = 3;
+        let points = vec![CompressedRistretto::default(); COUNT];
+        let mut input = Vec::with_capacity(COUNT * ppoprf::COMPRESSED_POINT_LEN);
+        for point in &points {
+            input.extend_from_slice(point.as_bytes());
+        }
+        let mut output = vec![0u8; COUNT * ppoprf::COMPRESSED_POINT_LEN];
+        unsafe {
+            let successes = randomness_server_eval_batch(
+                server,
+                input.as_ptr(),
+                COUNT,
+                0,
+                false,
+                output.as_mut_ptr(),
+            );
+            assert_eq!(successes, COUNT);
+            randomness_server_release(server);
+        }
+    }
+
     #[test]
     /// Verify serialization of internal types.
     fn serialization() {
@@ -1,6 +1,6 @@
 //! STAR Randomness web service
 
-use axum::{routing::get, routing::post, Router};
+use axum::{http::StatusCode, routing::get, routing::post, Router};
 use axum_prometheus::PrometheusMetricLayer;
 use calendar_duration::CalendarDuration;
 use clap::Parser;
@@ -9,7 +9,7 @@ use rlimit::Resource;
 use state::{OPRFServer, OPRFState};
 use tikv_jemallocator::Jemalloc;
 use time::OffsetDateTime;
-use tracing::{debug, info, metadata::LevelFilter};
+use tracing::{debug, info, metadata::LevelFilter, warn};
 use tracing_subscriber::EnvFilter;
 use util::{assert_unique_names, parse_timestamp};
 
@@ -17,7 +17,11 @@ use util::{assert_unique_names, parse_timestamp};
 static GLOBAL: Jemalloc = Jemalloc;
 
 mod handler;
+mod instance;
+mod metrics;
+mod result;
 mod state;
+mod transparency;
 mod util;
 
 #[cfg(test)]
@@ -61,12 +65,78 @@ pub struct Config {
     /// Enable prometheus metric reporting and listen on specified address.
     #[arg(long)]
     prometheus_listen: Option<String>,
+    /// Enable enclave key sync through a nitriding sidecar. When set, OPRF
+    /// instances are not created at startup; instead this node waits for
+    /// either a GET (leader) or PUT (worker) on /enclave/state to learn
+    /// whether it should generate or receive PPOPRF keys.
+    #[arg(long, default_value_t = false)]
+    enclave_key_sync: bool,
+    /// Port nitriding listens on for internal traffic. Required when
+    /// enclave-key-sync is set, since the leader pushes key updates there.
+    #[arg(long)]
+    nitriding_internal_port: Option<u16>,
+    /// Shared secret used to derive a static X25519 identity for the
+    /// enclave key-sync channel. Every node configured with the same
+    /// secret derives the same keypair and trusts only that key.
+    /// Mutually exclusive with --channel-trusted-peer.
+    #[arg(long)]
+    channel_psk: Option<String>,
+    /// Hex-encoded X25519 public key of a peer trusted on the enclave
+    /// key-sync channel. May be given multiple times. When set, this
+    /// node generates its own random static keypair rather than deriving
+    /// one from --channel-psk.
+    #[arg(long = "channel-trusted-peer")]
+    channel_trusted_peers: Vec<String>,
+    /// Rekey the enclave key-sync channel after this many sealed
+    /// messages.
+    #[arg(long, default_value_t = 10_000)]
+    channel_rekey_messages: u64,
+    /// Rekey the enclave key-sync channel after this much time has
+    /// elapsed since the last handshake, whichever comes first.
+    #[arg(long = "channel-rekey-interval", default_value = "1h")]
+    channel_rekey_interval: CalendarDuration,
+    /// Number of worker threads for the tokio runtime. Defaults to the
+    /// number of CPU cores when unset.
+    #[arg(long)]
+    worker_threads: Option<usize>,
+    /// Number of recent (epoch, point) evaluations to cache per instance,
+    /// so repeated client nonces skip the scalar multiplication. 0
+    /// disables the cache.
+    #[arg(long, default_value_t = 0)]
+    eval_cache_size: usize,
+    /// How long to wait for in-flight requests to finish during a
+    /// graceful shutdown before forcing the process to exit anyway.
+    #[arg(long = "shutdown-grace", value_name = "Duration string i.e. 30s", default_value = "30s")]
+    shutdown_grace: CalendarDuration,
+    /// Maximum time a single request may take before it is answered with
+    /// 408 Request Timeout.
+    #[arg(long = "request-timeout", value_name = "Duration string i.e. 30s", default_value = "30s")]
+    request_timeout: CalendarDuration,
+    /// Maximum accepted request body size, in bytes, rejected before the
+    /// body is buffered or parsed as JSON. A single MAX_POINTS-sized
+    /// request is a few tens of KB once base64-encoded, so this mostly
+    /// guards against a client trying to exhaust memory with an
+    /// oversized body rather than a legitimately large request.
+    #[arg(long, default_value_t = 1_048_576)]
+    max_request_body_bytes: usize,
+    /// Enable TCP keepalive probes on accepted connections.
+    #[arg(long, default_value_t = true)]
+    tcp_keepalive: bool,
+    /// Idle time before the first TCP keepalive probe is sent, once
+    /// enabled.
+    #[arg(long = "tcp-keepalive-time", value_name = "Duration string i.e. 60s", default_value = "60s")]
+    tcp_keepalive_time: CalendarDuration,
+    /// Disable Nagle's algorithm on accepted connections, trading a
+    /// small bandwidth overhead for lower latency on our small
+    /// request/response bodies.
+    #[arg(long, default_value_t = true)]
+    tcp_nodelay: bool,
 }
 
 /// Initialize an axum::Router for our web service
 /// Having this as a separate function makes testing easier.
-fn app(oprf_state: OPRFState) -> Router {
-    Router::new()
+fn app(config: &Config, oprf_state: OPRFState) -> Router {
+    let mut router = Router::new()
         // Friendly default route to identify the site
         .route("/", get(|| async { "STAR randomness server\n" }))
         // Endpoints for all instances
@@ -78,14 +148,60 @@ fn app(oprf_state: OPRFState) -> Router {
             "/instances/:instance/info",
             get(handler::specific_instance_info),
         )
+        .route(
+            "/instances/:instance/transparency/root",
+            get(handler::specific_instance_transparency_root),
+        )
+        .route(
+            "/instances/:instance/transparency/proof",
+            get(handler::specific_instance_transparency_proof),
+        )
         .route("/instances", get(handler::list_instances))
+        // Liveness/readiness probes for orchestrators
+        .route("/health", get(handler::health))
+        .route("/status", get(handler::status))
         // Endpoints for default instance
         .route("/randomness", post(handler::default_instance_randomness))
+        .route("/batch", post(handler::batch_randomness))
         .route("/info", get(handler::default_instance_info))
+        .route(
+            "/transparency/root",
+            get(handler::default_instance_transparency_root),
+        )
+        .route(
+            "/transparency/proof",
+            get(handler::default_instance_transparency_proof),
+        );
+
+    if config.enclave_key_sync {
+        router = router
+            .route(
+                "/enclave/state",
+                get(handler::get_ppoprf_private_key).put(handler::set_ppoprf_private_key),
+            )
+            .route("/enclave/handshake", post(handler::enclave_handshake));
+    }
+
+    let now = time::OffsetDateTime::now_utc();
+    let request_timeout = (now + config.request_timeout - now).unsigned_abs();
+
+    router
         // Attach shared state
         .with_state(oprf_state)
         // Logging must come after active routes
         .layer(tower_http::trace::TraceLayer::new_for_http())
+        // Reject oversized bodies before they're buffered or parsed as
+        // JSON, ahead of the per-request timeout below.
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(
+            config.max_request_body_bytes,
+        ))
+        .layer(
+            tower::ServiceBuilder::new()
+                .layer(axum::error_handling::HandleErrorLayer::new(
+                    |_: tower::BoxError| async { StatusCode::REQUEST_TIMEOUT },
+                ))
+                .layer(tower_http::timeout::TimeoutLayer::new(request_timeout)),
+        )
 }
 
 fn start_prometheus_server(metrics_handle: PrometheusHandle, listen: String) {
@@ -94,13 +210,49 @@ fn start_prometheus_server(metrics_handle: PrometheusHandle, listen: String) {
         let metrics_app =
             Router::new().route("/metrics", get(|| async move { metrics_handle.render() }));
         info!("Metrics server listening on {}", &listen);
-        axum::Server::bind(&addr)
-            .serve(metrics_app.into_make_service())
+        let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+        axum::serve(listener, metrics_app.into_make_service())
             .await
             .unwrap();
     });
 }
 
+/// Bind the listening socket with `--tcp-keepalive`/`--tcp-nodelay`
+/// applied, rather than via `TcpListener::bind`, so those settings are
+/// in place on the listening socket before any connection is accepted.
+fn bind_listener(config: &Config, addr: std::net::SocketAddr) -> tokio::net::TcpListener {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(addr),
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )
+    .expect("failed to create listening socket");
+    socket
+        .set_reuse_address(true)
+        .expect("failed to set SO_REUSEADDR");
+    if config.tcp_nodelay {
+        socket
+            .set_nodelay(true)
+            .expect("failed to set TCP_NODELAY");
+    }
+    if config.tcp_keepalive {
+        let now = time::OffsetDateTime::now_utc();
+        let keepalive_time = (now + config.tcp_keepalive_time - now).unsigned_abs();
+        socket
+            .set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(keepalive_time))
+            .expect("failed to set SO_KEEPALIVE");
+    }
+    socket.bind(&addr.into()).expect("failed to bind listener");
+    socket
+        .listen(1024)
+        .expect("failed to listen on bound socket");
+    socket
+        .set_nonblocking(true)
+        .expect("failed to set listener non-blocking");
+    tokio::net::TcpListener::from_std(socket.into())
+        .expect("failed to hand listener to tokio")
+}
+
 fn increase_nofile_limit() {
     let curr_limits =
         rlimit::getrlimit(Resource::NOFILE).expect("should be able to get current nofile limit");
@@ -115,8 +267,34 @@ fn increase_nofile_limit() {
     );
 }
 
-#[tokio::main]
-async fn main() {
+/// Wait for either SIGINT (Ctrl+C) or SIGTERM, whichever arrives first.
+/// Passed to `axum::serve`'s graceful shutdown, which then stops accepting
+/// new connections and lets in-flight requests finish.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("shutdown signal received, stopping gracefully");
+}
+
+fn main() {
     // Start logging
     // The default subscriber respects filter directives like `RUST_LOG=info`
     tracing_subscriber::fmt()
@@ -132,6 +310,21 @@ async fn main() {
     // Command line switches
     let config = Config::parse();
     debug!(?config, "config parsed");
+
+    // Build the tokio runtime explicitly so --worker-threads can size it,
+    // rather than relying on the #[tokio::main] default.
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    if let Some(worker_threads) = config.worker_threads {
+        builder.worker_threads(worker_threads);
+    }
+    let runtime = builder
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+    runtime.block_on(run(config));
+}
+
+async fn run(config: Config) {
     let addr = config.listen.parse().unwrap();
 
     if config.increase_nofile_limit {
@@ -151,6 +344,14 @@ async fn main() {
         config.instance_names.len() == config.epoch_durations.len(),
         "instance-name switch count must match epoch-seconds switch count"
     );
+    assert!(
+        !config.enclave_key_sync || config.nitriding_internal_port.is_some(),
+        "nitriding-internal-port must be set when enclave-key-sync is enabled"
+    );
+    assert!(
+        config.channel_psk.is_none() || config.channel_trusted_peers.is_empty(),
+        "channel-psk and channel-trusted-peer are mutually exclusive"
+    );
 
     let metric_layer = config.prometheus_listen.as_ref().map(|listen| {
         let (layer, handle) = PrometheusMetricLayer::pair();
@@ -158,20 +359,48 @@ async fn main() {
         layer
     });
 
-    let oprf_state = OPRFServer::new(&config);
-    oprf_state.start_background_tasks(&config);
+    let oprf_state = OPRFServer::new(config.clone()).await;
+    let shutdown_state = oprf_state.clone();
 
     // Set up routes and middleware
     info!("initializing routes...");
-    let mut app = app(oprf_state);
+    let mut app = app(&config, oprf_state);
     if let Some(metric_layer) = metric_layer {
         app = app.layer(metric_layer);
     }
 
     // Start the server
     info!("Listening on {}", &addr);
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+    let listener = bind_listener(&config, addr);
+    let serve = axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal());
+
+    // with_graceful_shutdown waits indefinitely for in-flight requests to
+    // finish once a shutdown signal arrives, and shutdown_state.shutdown()
+    // below can itself block indefinitely on a stuck nitriding connection
+    // or a wedged epoch-advance task; bound the *whole* sequence by one
+    // shared --shutdown-grace deadline so a stuck handler or task can't
+    // block the process from exiting forever.
+    let now = time::OffsetDateTime::now_utc();
+    let shutdown_grace = (now + config.shutdown_grace - now).unsigned_abs();
+    let shutdown_deadline = tokio::time::Instant::now() + shutdown_grace;
+    match tokio::time::timeout_at(shutdown_deadline, serve).await {
+        Ok(result) => result.unwrap(),
+        Err(_) => warn!("shutdown grace period elapsed with requests still in flight"),
+    }
+
+    // New /randomness requests are no longer accepted and in-flight ones
+    // have finished (or been abandoned after the grace period); let the
+    // epoch-advance and nitriding-sync tasks know so they can terminate
+    // cleanly, perform one final key flush, and drop each instance's
+    // PPOPRF key material so it doesn't linger in memory until the
+    // process is reaped. This shares the same deadline as the HTTP drain
+    // above rather than getting a fresh grace period of its own.
+    info!("stopped accepting connections, flushing key state...");
+    if tokio::time::timeout_at(shutdown_deadline, shutdown_state.shutdown())
         .await
-        .unwrap();
+        .is_err()
+    {
+        warn!("shutdown grace period elapsed while flushing key state");
+    }
 }
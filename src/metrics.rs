@@ -0,0 +1,65 @@
+//! Prometheus metrics for epoch rotation and enclave key-sync health.
+//!
+//! These are plain wrappers around the `metrics` macros so the call sites
+//! in `state.rs` stay readable and the metric names/labels live in one
+//! place. Recording is a no-op until `--prometheus-listen` installs a
+//! recorder via `PrometheusMetricLayer::pair()` in `main.rs`, so these can
+//! be called unconditionally from the hot paths without checking whether
+//! metrics are enabled.
+
+/// Current epoch tag for an instance. Lets an alert compare this against
+/// the epoch an operator expects the enclave to have reached.
+pub fn set_epoch(instance_name: &str, epoch: u8) {
+    metrics::gauge!("star_randsrv_epoch", "instance" => instance_name.to_string())
+        .set(epoch as f64);
+}
+
+/// Seconds remaining until `next_epoch_time`, recorded every time
+/// `epoch_loop` computes it. Goes negative when the enclave has fallen
+/// behind its rotation schedule, which is exactly the condition an
+/// operator wants to alert on.
+pub fn set_next_epoch_seconds(instance_name: &str, seconds: f64) {
+    metrics::gauge!("star_randsrv_next_epoch_seconds", "instance" => instance_name.to_string())
+        .set(seconds);
+}
+
+/// Incremented every time `epoch_loop` punctures the current epoch and
+/// advances (whether or not that advance also required a full key
+/// rotation).
+pub fn incr_epoch_advance(instance_name: &str) {
+    metrics::counter!("star_randsrv_epoch_advances_total", "instance" => instance_name.to_string())
+        .increment(1);
+}
+
+/// Incremented when `epoch_loop` exhausts the configured epoch range and
+/// generates a fresh PPOPRF key, rather than simply advancing to the next
+/// already-initialized epoch.
+pub fn incr_key_rotation(instance_name: &str) {
+    metrics::counter!("star_randsrv_key_rotations_total", "instance" => instance_name.to_string())
+        .increment(1);
+}
+
+/// Whether this node has settled into the leader (1) or worker (0) role
+/// for enclave key sync.
+pub fn set_leader_role(is_leader: bool) {
+    metrics::gauge!("star_randsrv_is_leader").set(if is_leader { 1.0 } else { 0.0 });
+}
+
+/// Incremented on every `OPRFServer::get_private_keys` call (leader
+/// export path), and again on failure, so key-sync stalls show up as the
+/// failures counter rising alongside the total.
+pub fn incr_get_private_keys(success: bool) {
+    metrics::counter!("star_randsrv_get_private_keys_total").increment(1);
+    if !success {
+        metrics::counter!("star_randsrv_get_private_keys_failures_total").increment(1);
+    }
+}
+
+/// Incremented on every `OPRFServer::set_private_keys` call (worker
+/// import path), and again on failure.
+pub fn incr_set_private_keys(success: bool) {
+    metrics::counter!("star_randsrv_set_private_keys_total").increment(1);
+    if !success {
+        metrics::counter!("star_randsrv_set_private_keys_failures_total").increment(1);
+    }
+}
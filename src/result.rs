@@ -22,6 +22,18 @@ pub enum Error {
     InvalidPrivateKeyCall,
     #[error("PPOPRF not ready")]
     PPOPRFNotReady,
+    #[error("enclave-sync peer is not in the trusted key set")]
+    ChannelUntrustedPeer,
+    #[error("enclave-sync message failed authentication or decryption")]
+    ChannelCryptoFailure,
+    #[error("enclave-sync message counter {0} is a replay or outside the window")]
+    ChannelReplay(u64),
+    #[error("enclave key-sync protocol version {0} is not supported by this node")]
+    IncompatibleKeySyncVersion(u32),
+    #[error("no transparency log entry for epoch {0} under the given public key")]
+    EpochNotCommitted(u8),
+    #[error("batched evaluation proofs are not available on this server")]
+    ProofUnavailable,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -7,19 +7,26 @@ use std::{
     collections::{BTreeMap, HashMap},
     sync::Arc,
 };
-use tokio::sync::{OnceCell, RwLock};
+use tokio::sync::{watch, Mutex, OnceCell, RwLock};
 use tracing::{error, info, instrument};
 
 use crate::{
-    instance::OPRFInstance,
+    instance::{self, OPRFInstance},
+    metrics,
     result::{Error, Result},
 };
 use crate::{
-    util::{format_rfc3339, parse_timestamp, send_private_keys_to_nitriding},
+    util::{
+        format_rfc3339, parse_timestamp, perform_handshake_over_nitriding,
+        send_private_keys_to_nitriding,
+    },
     Config,
 };
 use ppoprf::ppoprf;
 
+pub mod channel;
+pub mod push;
+
 /// Container for OPRF instances
 pub struct OPRFServer {
     /// All OPRF instances, keyed by instance name
@@ -35,6 +42,23 @@ pub struct OPRFServer {
     /// If set, the state will reflect the leader/worker status
     /// of the server.
     pub is_leader: OnceCell<bool>,
+    /// Static identity and trust policy for the enclave key-sync secure
+    /// channel, derived from `--channel-psk`/`--channel-trusted-peer`.
+    /// `None` if neither is configured, in which case key sync falls
+    /// back to the legacy plaintext transfer.
+    pub channel_identity: Option<channel::ChannelIdentity>,
+    /// The currently-established secure channel to the peer enclave, if
+    /// a handshake has completed. Re-established on demand when absent
+    /// or when [`channel::SecureChannel::needs_rekey`] is true.
+    pub channel: Mutex<Option<channel::SecureChannel>>,
+    /// Supervises delivery of private-key updates to nitriding with
+    /// reconnection, jittered backoff, and update coalescing. Only
+    /// spawned once this node becomes the leader and has a key update
+    /// to send.
+    pub nitriding_push: OnceCell<push::NitridingPush>,
+    /// Signals graceful shutdown to the epoch-advance and nitriding-sync
+    /// background tasks. Flips to `true` once, from [`OPRFServer::shutdown`].
+    pub shutdown: watch::Sender<bool>,
 }
 
 /// Arc wrapper for OPRFServer
@@ -64,6 +88,72 @@ pub type OPRFKeys = BTreeMap<String, KeyInfo>;
 /// Used when getting keys for serialization.
 pub type OPRFKeysRef<'a> = BTreeMap<String, KeyInfoRef<'a>>;
 
+/// Current enclave key-sync wire format version. Bump this whenever the
+/// envelope or the inner `OPRFKeys` schema changes, and add a matching
+/// arm to [`KeySyncEnvelope::decode_keys`] so a worker can still decode
+/// the previous version's payloads during a rolling deploy.
+const KEY_SYNC_PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest key-sync protocol version this node still knows how to decode.
+/// Payloads tagged with anything outside
+/// `MIN_SUPPORTED_KEY_SYNC_VERSION..=KEY_SYNC_PROTOCOL_VERSION` are
+/// rejected with [`Error::IncompatibleKeySyncVersion`] rather than risking
+/// a misinterpreted schema.
+const MIN_SUPPORTED_KEY_SYNC_VERSION: u32 = 1;
+
+/// Wraps a bincode-serialized `OPRFKeys`/`OPRFKeysRef` payload with the
+/// protocol version it was produced under, so a worker mid-rolling-deploy
+/// can tell a schema it no longer understands apart from one it can still
+/// decode, instead of attempting the inner `bincode::deserialize` blind.
+/// Framed manually (4-byte little-endian version, then the raw bincode
+/// payload) rather than derived, matching the wire format used elsewhere
+/// in enclave key-sync (see `channel::HandshakeInit`).
+pub(crate) struct KeySyncEnvelope<'a> {
+    protocol_version: u32,
+    payload: &'a [u8],
+}
+
+impl<'a> KeySyncEnvelope<'a> {
+    /// Frame `payload` (already serialized by the caller) under the
+    /// current protocol version.
+    pub(crate) fn encode(payload: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + payload.len());
+        bytes.extend_from_slice(&KEY_SYNC_PROTOCOL_VERSION.to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// Split `bytes` into its version header and payload, rejecting
+    /// versions outside the supported range before the inner payload is
+    /// ever touched.
+    pub(crate) fn decode(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < 4 {
+            return Err(Error::IncompatibleKeySyncVersion(0));
+        }
+        let (version_bytes, payload) = bytes.split_at(4);
+        let protocol_version = u32::from_le_bytes(version_bytes.try_into().unwrap());
+        if !(MIN_SUPPORTED_KEY_SYNC_VERSION..=KEY_SYNC_PROTOCOL_VERSION).contains(&protocol_version)
+        {
+            return Err(Error::IncompatibleKeySyncVersion(protocol_version));
+        }
+        Ok(KeySyncEnvelope {
+            protocol_version,
+            payload,
+        })
+    }
+
+    /// Deserialize the framed payload as `OPRFKeys`, dispatching on
+    /// `protocol_version` so a future schema bump can add an arm here for
+    /// the previous version's format rather than breaking workers that
+    /// haven't redeployed yet.
+    pub(crate) fn decode_keys(&self) -> Result<OPRFKeys> {
+        match self.protocol_version {
+            1 => bincode::deserialize(self.payload).map_err(Error::KeySerialization),
+            other => Err(Error::IncompatibleKeySyncVersion(other)),
+        }
+    }
+}
+
 impl OPRFServer {
     /// Initialize all OPRF instances with given configuration
     pub async fn new(config: Config) -> Arc<Self> {
@@ -82,11 +172,16 @@ impl OPRFServer {
             instances.insert(instance_name.to_string(), RwLock::new(instance));
         }
         let enclave_key_sync_enabled = config.enclave_key_sync;
+        let channel_identity = channel::identity_from_config(&config);
         let server = Arc::new(OPRFServer {
             instances,
             default_instance: config.instance_names.first().cloned().unwrap(),
             config,
             is_leader: Default::default(),
+            channel_identity,
+            channel: Mutex::new(None),
+            nitriding_push: OnceCell::new(),
+            shutdown: watch::channel(false).0,
         });
         if !enclave_key_sync_enabled {
             for instance_name in &server.config.instance_names {
@@ -134,12 +229,25 @@ impl OPRFServer {
 
         let epochs = self.config.first_epoch..=self.config.last_epoch;
 
+        let mut shutdown = self.shutdown.subscribe();
+
         loop {
-            // Wait until the current epoch ends.
+            // Wait until the current epoch ends, or until shutdown is
+            // signaled, whichever comes first, so a draining enclave
+            // exits promptly instead of running to the next rotation.
             let sleep_duration = next_epoch_time - time::OffsetDateTime::now_utc();
-            // Negative durations mean we're behind.
+            metrics::set_next_epoch_seconds(&instance_name, sleep_duration.as_seconds_f64());
             if sleep_duration.is_positive() {
-                tokio::time::sleep(sleep_duration.unsigned_abs()).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(sleep_duration.unsigned_abs()) => {}
+                    _ = shutdown.changed() => {
+                        info!(instance_name, "shutdown signaled, exiting epoch loop");
+                        return;
+                    }
+                }
+            } else if *shutdown.borrow() {
+                info!(instance_name, "shutdown signaled, exiting epoch loop");
+                return;
             }
             next_epoch_time = next_epoch_time + epoch_duration;
 
@@ -155,6 +263,13 @@ impl OPRFServer {
                 s.server
                     .puncture(old_epoch)
                     .expect("Failed to puncture current epoch");
+                metrics::incr_epoch_advance(&instance_name);
+                // The punctured epoch's cached outputs can never be
+                // served again; drop them rather than let them sit
+                // until capacity evicts them.
+                if let Some(cache) = &s.eval_cache {
+                    cache.clear();
+                }
 
                 // Advance to the next epoch, checking for overflow
                 // and out-of-range.
@@ -162,6 +277,9 @@ impl OPRFServer {
                 if new_epoch.filter(|e| epochs.contains(e)).is_some() {
                     // Server is already initialized for this one.
                     s.epoch = new_epoch.unwrap();
+                    let public_key = instance::public_key_bytes(&s.server)
+                        .expect("failed to serialize public key for transparency log");
+                    s.transparency_log.append(s.epoch, &public_key);
                 } else {
                     if let Some(false) = self.is_leader.get() {
                         info!("Epochs exhausted, exiting background task. New task will start after leader shares new key.");
@@ -169,29 +287,41 @@ impl OPRFServer {
                         return;
                     } else {
                         info!("Epochs exhausted! Rotating OPRF key");
+                        metrics::incr_key_rotation(&instance_name);
+                        // Carry the transparency log forward across the
+                        // rotation; leaves are never removed, even though
+                        // the PPOPRF key material underneath is replaced.
+                        let mut transparency_log = std::mem::take(&mut s.transparency_log);
                         // Panics if this fails. Puncture should mean we can't
                         // violate privacy through further evaluations, but we
                         // still want to drop the inner state with its private key.
                         *s = OPRFInstance::new(&self.config, &instance_name, true)
                             .expect("Could not initialize new PPOPRF server");
+                        let public_key = instance::public_key_bytes(&s.server)
+                            .expect("failed to serialize public key for transparency log");
+                        transparency_log.append(s.epoch, &public_key);
+                        s.transparency_log = transparency_log;
                     }
                 }
                 s.next_epoch_time = format_rfc3339(&next_epoch_time);
+                metrics::set_epoch(&instance_name, s.epoch);
                 info!("epoch now {}, next rotation = {next_epoch_time}", s.epoch);
             }
 
             if self.config.enclave_key_sync {
                 if let Some(true) = self.is_leader.get() {
                     // Since a new OPRFInstance was created, we should sync the new key
-                    // to other enclaves if key sync is enabled.
-                    send_private_keys_to_nitriding(
-                        self.config.nitriding_internal_port.unwrap(),
-                        self.get_private_keys()
-                            .await
-                            .expect("failed to get private keys to send to nitriding"),
-                    )
-                    .await
-                    .expect("failed to send updated private keys to nitriding");
+                    // to other enclaves if key sync is enabled. Hand it off to the
+                    // supervised push task rather than sending inline, so a transient
+                    // nitriding outage while delivering the payload is retried instead
+                    // of lost or panicking here. Collecting the payload is not retried:
+                    // a failure there (with the secure channel configured, that
+                    // includes the handshake over nitriding) panics on the expect below.
+                    let payload = self
+                        .get_private_keys()
+                        .await
+                        .expect("failed to get private keys to send to nitriding");
+                    self.nitriding_push().await.push(payload);
                 }
             }
         }
@@ -201,6 +331,12 @@ impl OPRFServer {
     /// If this method is called, this server will assume that it is a worker.
     /// OPRFInstances will be created, if not created already.
     pub async fn set_private_keys(self: &Arc<Self>, private_keys_bytes: Bytes) -> Result<()> {
+        let result = self.set_private_keys_inner(private_keys_bytes).await;
+        metrics::incr_set_private_keys(result.is_ok());
+        result
+    }
+
+    async fn set_private_keys_inner(self: &Arc<Self>, private_keys_bytes: Bytes) -> Result<()> {
         assert!(self.config.enclave_key_sync);
         if let Some(true) = self.is_leader.get() {
             error!("invalid set_private_keys call on leader");
@@ -210,9 +346,18 @@ impl OPRFServer {
             self.is_leader
                 .set(false)
                 .expect("failed to set leader status");
+            metrics::set_leader_role(false);
         }
-        let private_keys: OPRFKeys =
-            bincode::deserialize(&private_keys_bytes).map_err(|e| Error::KeySerialization(e))?;
+        let plaintext = if self.channel_identity.is_some() {
+            let mut channel_guard = self.channel.lock().await;
+            let channel = channel_guard
+                .as_mut()
+                .ok_or(Error::ChannelCryptoFailure)?;
+            channel.open(&private_keys_bytes)?
+        } else {
+            private_keys_bytes.to_vec()
+        };
+        let private_keys = KeySyncEnvelope::decode(&plaintext)?.decode_keys()?;
         for (instance_name, key_info) in private_keys {
             if let Some(instance) = self.instances.get(&instance_name) {
                 {
@@ -281,6 +426,12 @@ impl OPRFServer {
     /// Exports keys so that nitriding and forward the keys to worker enclaves.
     /// If this method is called, the server will assume that it is the leader.
     pub async fn get_private_keys(self: &Arc<Self>) -> Result<Vec<u8>> {
+        let result = self.get_private_keys_inner().await;
+        metrics::incr_get_private_keys(result.is_ok());
+        result
+    }
+
+    async fn get_private_keys_inner(self: &Arc<Self>) -> Result<Vec<u8>> {
         assert!(self.config.enclave_key_sync);
         if let Some(false) = self.is_leader.get() {
             error!("invalid get_private_keys call on worker");
@@ -290,6 +441,7 @@ impl OPRFServer {
             self.is_leader
                 .set(true)
                 .expect("failed to set leader status");
+            metrics::set_leader_role(true);
         }
         let mut server_guards = Vec::with_capacity(self.instances.len());
         for (instance_name, instance) in &self.instances {
@@ -307,6 +459,133 @@ impl OPRFServer {
                 },
             );
         }
-        bincode::serialize(&private_keys).map_err(|e| Error::KeySerialization(e))
+        let serialized =
+            bincode::serialize(&private_keys).map_err(|e| Error::KeySerialization(e))?;
+        let plaintext = KeySyncEnvelope::encode(&serialized);
+
+        if let Some(identity) = &self.channel_identity {
+            self.ensure_channel_as_initiator(identity).await?;
+            let mut channel_guard = self.channel.lock().await;
+            let channel = channel_guard
+                .as_mut()
+                .expect("channel was just established above");
+            channel.seal(&plaintext)
+        } else {
+            Ok(plaintext)
+        }
+    }
+
+    /// Make sure a usable (not due for rekey) secure channel is
+    /// established to the peer enclave, performing a fresh handshake
+    /// over nitriding if none exists yet or the current one is due for
+    /// rekey.
+    async fn ensure_channel_as_initiator(
+        self: &Arc<Self>,
+        identity: &channel::ChannelIdentity,
+    ) -> Result<()> {
+        let needs_handshake = {
+            let channel_guard = self.channel.lock().await;
+            match channel_guard.as_ref() {
+                Some(channel) => channel.needs_rekey(),
+                None => true,
+            }
+        };
+        if !needs_handshake {
+            return Ok(());
+        }
+        let rekey = channel::rekey_policy_from_config(&self.config);
+        let established = perform_handshake_over_nitriding(
+            self.config
+                .nitriding_internal_port
+                .expect("nitriding-internal-port must be set when enclave-key-sync is enabled"),
+            identity,
+            rekey,
+        )
+        .await?;
+        *self.channel.lock().await = Some(established);
+        Ok(())
+    }
+
+    /// Access the supervised push task that delivers key updates to
+    /// nitriding, spawning it on first use.
+    async fn nitriding_push(self: &Arc<Self>) -> &push::NitridingPush {
+        self.nitriding_push
+            .get_or_init(|| async {
+                push::NitridingPush::spawn(
+                    self.config.nitriding_internal_port.expect(
+                        "nitriding-internal-port must be set when enclave-key-sync is enabled",
+                    ),
+                    self.shutdown.subscribe(),
+                )
+            })
+            .await
+    }
+
+    /// Signal graceful shutdown to the epoch-advance and nitriding-sync
+    /// background tasks, performing one final key-state flush to
+    /// nitriding first if this node is the leader, then wait for every
+    /// epoch loop to exit and drop each instance's private key material.
+    pub async fn shutdown(self: &Arc<Self>) {
+        if let (true, Some(port)) = (
+            matches!(self.is_leader.get(), Some(true)),
+            self.config.nitriding_internal_port,
+        ) {
+            match self.get_private_keys().await {
+                Ok(payload) => {
+                    if let Err(error) = send_private_keys_to_nitriding(port, payload).await {
+                        error!(%error, "final key-state flush to nitriding failed");
+                    }
+                }
+                Err(error) => error!(%error, "failed to collect private keys for final flush"),
+            }
+        }
+        // Wake the epoch-advance and nitriding-sync tasks so they exit
+        // cleanly instead of being aborted mid-operation.
+        let _ = self.shutdown.send(true);
+
+        // Wait for every epoch-loop task to actually observe the signal
+        // and return before we let the process exit, rather than just
+        // detaching them, so a rotation in flight always finishes its
+        // puncture instead of being torn down mid-way.
+        let mut handles = Vec::with_capacity(self.instances.len());
+        for instance in self.instances.values() {
+            let mut instance_guard = instance.write().await;
+            if let Some(instance) = instance_guard.as_mut() {
+                if let Some(handle) = instance.background_task_handle.take() {
+                    handles.push(handle);
+                }
+            }
+        }
+        for handle in handles {
+            if let Err(error) = handle.await {
+                error!(%error, "epoch-loop task panicked during shutdown");
+            }
+        }
+
+        // Every epoch-loop task has stopped touching its instance now;
+        // drop the PPOPRF server (and with it the private key material
+        // and cached evaluations) for each one rather than letting it
+        // sit in memory until the process exits.
+        for instance in self.instances.values() {
+            *instance.write().await = None;
+        }
+    }
+
+    /// Responder side of the enclave key-sync handshake, called from the
+    /// `/enclave/handshake` endpoint. Stores the resulting channel so the
+    /// next `set_private_keys` call can decrypt with it.
+    pub async fn handle_handshake(
+        self: &Arc<Self>,
+        init_bytes: &[u8],
+    ) -> Result<Vec<u8>> {
+        let identity = self
+            .channel_identity
+            .as_ref()
+            .ok_or(Error::ChannelUntrustedPeer)?;
+        let init = channel::HandshakeInit::from_bytes(init_bytes)?;
+        let rekey = channel::rekey_policy_from_config(&self.config);
+        let (response, established) = channel::handshake_responder(identity, rekey, init)?;
+        *self.channel.lock().await = Some(established);
+        Ok(response.to_bytes().to_vec())
     }
 }
@@ -0,0 +1,114 @@
+//! Supervised, reconnecting delivery of private-key updates to nitriding.
+//!
+//! The leader enclave pushes a PUT to nitriding's internal port every time
+//! it rotates an epoch key. A bare one-shot PUT silently loses the update
+//! if nitriding or the relay is briefly unavailable, so instead we run a
+//! background task that retries a failed push with jittered exponential
+//! backoff up to a cap. Updates are coalesced rather than queued: if a new
+//! epoch's keys arrive while a previous push is still retrying, the new
+//! payload simply replaces the pending one, so a reconnect always delivers
+//! the latest epoch's keys rather than replaying a backlog.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::util::send_private_keys_to_nitriding;
+
+/// Initial delay before retrying a failed push.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+/// Upper bound on the retry backoff delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Add up to 50% random jitter to a backoff delay, so that many
+/// supervisors retrying at once don't all hammer nitriding in lockstep.
+fn jittered(delay: Duration) -> Duration {
+    let jitter = rand::thread_rng().gen_range(0.0..0.5);
+    delay.mul_f64(1.0 + jitter)
+}
+
+/// Handle to the background task supervising pushes to nitriding.
+pub struct NitridingPush {
+    /// Delivers (or replaces) the pending payload to the background task.
+    /// `None` is never sent; it's only the channel's initial value.
+    pending: watch::Sender<Option<Vec<u8>>>,
+    /// Whether the most recent push attempt succeeded, so a readiness
+    /// probe can reflect key-sync health.
+    healthy: Arc<AtomicBool>,
+}
+
+impl NitridingPush {
+    /// Spawn the supervisor task pushing to nitriding's internal port.
+    /// `shutdown` lets the task stop cleanly once the server begins a
+    /// graceful shutdown, rather than retrying forever; a final flush
+    /// on shutdown is handled separately by `OPRFServer::shutdown`.
+    pub fn spawn(nitriding_internal_port: u16, mut shutdown: watch::Receiver<bool>) -> Self {
+        let (pending, mut rx) = watch::channel(None::<Vec<u8>>);
+        let healthy = Arc::new(AtomicBool::new(true));
+        let task_healthy = healthy.clone();
+
+        tokio::spawn(async move {
+            loop {
+                // Wait for a pending update to show up, or for shutdown.
+                tokio::select! {
+                    changed = rx.changed() => {
+                        if changed.is_err() {
+                            // Sender dropped along with the OPRFServer;
+                            // nothing left to deliver.
+                            return;
+                        }
+                    }
+                    _ = shutdown.changed() => {
+                        return;
+                    }
+                }
+                let Some(payload) = rx.borrow_and_update().clone() else {
+                    continue;
+                };
+
+                let mut backoff = INITIAL_BACKOFF;
+                loop {
+                    match send_private_keys_to_nitriding(nitriding_internal_port, payload.clone())
+                        .await
+                    {
+                        Ok(()) => {
+                            task_healthy.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                        Err(error) => {
+                            task_healthy.store(false, Ordering::Relaxed);
+                            warn!(%error, ?backoff, "push to nitriding failed, retrying");
+                            tokio::time::sleep(jittered(backoff)).await;
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                    }
+                    // A fresher update superseded this one while we were
+                    // retrying; drop the stale payload and go push the
+                    // latest one instead.
+                    if rx.has_changed().unwrap_or(false) || *shutdown.borrow() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        NitridingPush { pending, healthy }
+    }
+
+    /// Queue `payload` for delivery, superseding any update that is
+    /// still pending or being retried.
+    pub fn push(&self, payload: Vec<u8>) {
+        // A closed receiver would mean the supervisor task panicked;
+        // there's nothing useful to do with the update but drop it.
+        let _ = self.pending.send(Some(payload));
+    }
+
+    /// Whether the most recent push attempt to nitriding succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+}
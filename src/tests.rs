@@ -1,6 +1,6 @@
 //! STAR Randomness web service tests
 
-use crate::state::{KeyInfoRef, OPRFKeys, OPRFKeysRef, OPRFServer};
+use crate::state::{KeyInfoRef, KeySyncEnvelope, OPRFKeysRef, OPRFServer};
 use axum::body::{to_bytes, Body, Bytes};
 use axum::extract::State;
 use axum::http::StatusCode;
@@ -58,6 +58,18 @@ async fn test_app(instance_configs: Option<Vec<InstanceConfig>>) -> crate::Route
             .collect(),
         enclave_key_sync: false,
         nitriding_internal_port: None,
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
     };
     // server state
     let oprf_state = OPRFServer::new(config.clone()).await;
@@ -172,6 +184,65 @@ async fn info() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn transparency_root_and_proof() {
+    let mut app = test_app(None).await;
+
+    let response = app
+        .call(test_request("/info", None, None))
+        .await
+        .unwrap();
+    let public_key_b64 = validate_info_response_and_return_public_key_b64(
+        response.status(),
+        to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap(),
+    );
+
+    let response = app
+        .call(test_request("/transparency/root", None, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(body.as_ref()).unwrap();
+    assert_eq!(json["leafCount"], json!(1));
+    let root = BASE64.decode(json["root"].as_str().unwrap()).unwrap();
+
+    let encoded_key = url_encode_query_value(&public_key_b64);
+    let proof_uri = format!("/transparency/proof?epoch={EPOCH}&public_key={encoded_key}");
+    let response = app.call(test_request(&proof_uri, None, None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(body.as_ref()).unwrap();
+    assert_eq!(json["leafIndex"], json!(0));
+    assert_eq!(json["root"].as_str().unwrap(), BASE64.encode(&root));
+    // A freshly-initialized instance has a single leaf, so its root is
+    // simply the leaf hash and the proof has no sibling steps.
+    assert!(json["steps"].as_array().unwrap().is_empty());
+
+    // An epoch that was never committed under this key should 404.
+    let missing_proof_uri =
+        format!("/transparency/proof?epoch={}&public_key={encoded_key}", EPOCH + 1);
+    let response = app
+        .call(test_request(&missing_proof_uri, None, None))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Percent-encode the handful of characters base64's standard alphabet can
+/// produce that would otherwise be misread as query-string syntax.
+fn url_encode_query_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '+' => "%2B".to_string(),
+            '/' => "%2F".to_string(),
+            '=' => "%3D".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
 #[tokio::test]
 async fn randomness() {
     let mut app = test_app(Some(vec![
@@ -237,6 +308,66 @@ async fn randomness() {
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn randomness_proof() {
+    let mut app = test_app(None).await;
+
+    let payload = json!({ "points": make_points(3), "prove": true }).to_string();
+    let request = test_request("/randomness", Some(payload.into()), None);
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+    let json: Value = serde_json::from_slice(&body).expect("response body should parse as json");
+    let proof = json["proof"].as_str().expect("proof field should be present");
+    assert!(!BASE64.decode(proof).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn randomness_proof_unavailable_while_streaming() {
+    // A constant-size proof doesn't fit the incremental ndjson path, so
+    // a combined prove+stream request is rejected rather than silently
+    // dropping the proof.
+    let payload = json!({ "points": make_points(3), "prove": true }).to_string();
+    let request = Request::builder()
+        .uri("/randomness")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/x-ndjson")
+        .body(payload.into())
+        .unwrap();
+    let response = test_app(None).await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn randomness_octet_stream() {
+    let app = test_app(None).await;
+
+    let point = RistrettoPoint::random(&mut OsRng);
+    let payload = json!({ "points": [
+        BASE64.encode(point.compress().as_bytes())
+    ]})
+    .to_string();
+
+    let request = Request::builder()
+        .uri("/randomness")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/octet-stream")
+        .body(payload.into())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/octet-stream"
+    );
+    let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+    verify_randomness_body_octets(&body, 1);
+}
+
 #[tokio::test]
 #[allow(clippy::assertions_on_constants)]
 async fn epoch() {
@@ -297,6 +428,18 @@ async fn epoch_base_time() {
         instance_names: vec!["main".to_string()],
         enclave_key_sync: false,
         nitriding_internal_port: None,
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
     };
     let expected_epoch = EPOCH + 1;
     let advance = Duration::from_secs(9);
@@ -349,6 +492,18 @@ fn verify_randomness_body(body: &Bytes, expected_points: usize) {
     }
 }
 
+/// Check a binary-encoded randomness response body for validity
+fn verify_randomness_body_octets(body: &Bytes, expected_points: usize) {
+    const POINT_LEN: usize = 32;
+    // epoch byte, followed by fixed-width compressed points with no
+    // base64 or json framing.
+    assert_eq!(body.len(), 1 + expected_points * POINT_LEN);
+    assert_eq!(body[0], EPOCH);
+    for chunk in body[1..].chunks_exact(POINT_LEN) {
+        let _ = CompressedRistretto::from_slice(chunk);
+    }
+}
+
 /// Generate a number of random base64-encoded points.
 fn make_points(count: usize) -> Vec<String> {
     let mut points = Vec::with_capacity(count);
@@ -398,6 +553,133 @@ async fn max_points() {
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test]
+async fn max_points_streaming() {
+    // Submit a MAX_POINTS batch over the streaming (ndjson) path and
+    // reassemble the per-point frames.
+    let points = make_points(crate::MAX_POINTS);
+    let payload = json!({ "points": points }).to_string();
+
+    let request = Request::builder()
+        .uri("/randomness")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/x-ndjson")
+        .body(payload.into())
+        .unwrap();
+    let response = test_app(None).await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/x-ndjson"
+    );
+
+    let body = to_bytes(response.into_body(), RESPONSE_MAX * 2)
+        .await
+        .unwrap();
+    let lines: Vec<&[u8]> = body.split(|&b| b == b'\n').filter(|l| !l.is_empty()).collect();
+    assert_eq!(lines.len(), points.len());
+    for line in lines {
+        let json: Value = serde_json::from_slice(line).expect("each frame should parse as json");
+        assert_eq!(json["epoch"], json!(EPOCH));
+        let b64point = json["point"].as_str().unwrap();
+        let rawpoint = BASE64.decode(b64point).unwrap();
+        let _ = CompressedRistretto::from_slice(&rawpoint);
+    }
+}
+
+#[tokio::test]
+async fn streaming_emits_terminal_error_on_mid_batch_failure() {
+    // A malformed point partway through the batch should truncate the
+    // stream with a terminal error line, rather than silently ending it
+    // with no way for the client to tell a cut-short stream from a
+    // complete one.
+    let mut points = make_points(2);
+    points.push(BASE64.encode(b"not a valid compressed point"));
+    points.extend(make_points(2));
+    let payload = json!({ "points": points }).to_string();
+
+    let request = Request::builder()
+        .uri("/randomness")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/x-ndjson")
+        .body(payload.into())
+        .unwrap();
+    let response = test_app(None).await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), RESPONSE_MAX * 2)
+        .await
+        .unwrap();
+    let lines: Vec<&[u8]> = body.split(|&b| b == b'\n').filter(|l| !l.is_empty()).collect();
+
+    // Two good points, then the terminal error line; nothing after it.
+    assert_eq!(lines.len(), 3);
+    for line in &lines[..2] {
+        let json: Value = serde_json::from_slice(line).expect("each frame should parse as json");
+        assert!(json["point"].is_string());
+    }
+    let last: Value = serde_json::from_slice(lines[2]).expect("terminal line should parse as json");
+    assert!(last["error"].is_string());
+}
+
+#[tokio::test]
+async fn batch() {
+    let mut app = test_app(Some(vec![
+        InstanceConfig {
+            instance_name: "main".to_string(),
+            epoch_duration: "1s".to_string(),
+        },
+        InstanceConfig {
+            instance_name: "alternate".to_string(),
+            epoch_duration: "1s".to_string(),
+        },
+    ]))
+    .await;
+
+    let payload = json!({ "entries": [
+        { "instance": "main", "points": make_points(2) },
+        { "instance": "alternate", "points": make_points(3) },
+        { "instance": "notexisting", "points": make_points(1) },
+    ]})
+    .to_string();
+    let request = test_request("/batch", Some(payload.into()), None);
+    let response = app.call(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+    let results: Vec<Value> =
+        serde_json::from_slice(&body).expect("response body should parse as json");
+    assert_eq!(results.len(), 3);
+
+    assert_eq!(results[0]["instance"], json!("main"));
+    assert_eq!(results[0]["epoch"], json!(EPOCH));
+    assert_eq!(results[0]["points"].as_array().unwrap().len(), 2);
+
+    assert_eq!(results[1]["instance"], json!("alternate"));
+    assert_eq!(results[1]["epoch"], json!(EPOCH));
+    assert_eq!(results[1]["points"].as_array().unwrap().len(), 3);
+
+    // An entry naming an unknown instance reports its own error rather
+    // than failing the rest of the batch.
+    assert_eq!(results[2]["instance"], json!("notexisting"));
+    assert!(results[2]["message"].as_str().unwrap().contains("notexisting"));
+    assert!(results[2].get("points").is_none());
+}
+
+#[tokio::test]
+async fn batch_enforces_total_point_budget() {
+    let payload = json!({ "entries": [
+        { "instance": "main", "points": make_points(crate::MAX_POINTS) },
+        { "instance": "main", "points": make_points(1) },
+    ]})
+    .to_string();
+    let request = test_request("/batch", Some(payload.into()), None);
+    let response = test_app(None).await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_enclave_leader() {
     let config = crate::Config {
@@ -411,6 +693,18 @@ async fn test_enclave_leader() {
         instance_names: vec!["main".to_string(), "secondary".to_string()],
         enclave_key_sync: true,
         nitriding_internal_port: Some(8083),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
     };
 
     let oprf_state = OPRFServer::new(config.clone()).await;
@@ -440,8 +734,10 @@ async fn test_enclave_leader() {
     let body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
     assert!(!body.is_empty());
 
-    let private_keys: OPRFKeys =
-        bincode::deserialize(&body).expect("Failed to deserialize private keys");
+    let private_keys = KeySyncEnvelope::decode(&body)
+        .expect("Failed to decode key-sync envelope")
+        .decode_keys()
+        .expect("Failed to deserialize private keys");
 
     assert_eq!(private_keys.len(), 2);
 
@@ -478,6 +774,18 @@ async fn test_enclave_worker() {
         instance_names: vec!["main".to_string(), "secondary".to_string()],
         enclave_key_sync: true,
         nitriding_internal_port: Some(8085),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
     };
 
     let oprf_state = OPRFServer::new(config.clone()).await;
@@ -521,7 +829,9 @@ async fn test_enclave_worker() {
         })
         .collect::<OPRFKeysRef>();
 
-    let mock_keys_bytes = bincode::serialize(&mock_keys).expect("Failed to serialize mock keys");
+    let mock_keys_serialized =
+        bincode::serialize(&mock_keys).expect("Failed to serialize mock keys");
+    let mock_keys_bytes = KeySyncEnvelope::encode(&mock_keys_serialized);
 
     let app = crate::app(&config, oprf_state.clone());
 
@@ -546,6 +856,224 @@ async fn test_enclave_worker() {
     }
 }
 
+#[tokio::test]
+async fn test_enclave_worker_rejects_incompatible_key_sync_version() {
+    let config = crate::Config {
+        listen: "127.0.0.1:8086".to_string(),
+        epoch_durations: vec!["1s".into(), "2s".into()],
+        first_epoch: EPOCH,
+        last_epoch: EPOCH * 2,
+        epoch_base_time: None,
+        increase_nofile_limit: false,
+        prometheus_listen: None,
+        instance_names: vec!["main".to_string(), "secondary".to_string()],
+        enclave_key_sync: true,
+        nitriding_internal_port: Some(8093),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
+    };
+
+    let oprf_state = OPRFServer::new(config.clone()).await;
+
+    let mock_ppoprfs = config
+        .instance_names
+        .iter()
+        .map(|instance_name| {
+            (
+                instance_name,
+                ppoprf::ppoprf::Server::new((EPOCH..EPOCH * 2).collect()).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mock_keys = mock_ppoprfs
+        .iter()
+        .map(|(instance_name, server)| {
+            (
+                instance_name.to_string(),
+                KeyInfoRef {
+                    key_state: server.get_private_key(),
+                    epoch: EPOCH,
+                },
+            )
+        })
+        .collect::<OPRFKeysRef>();
+    let mock_keys_serialized =
+        bincode::serialize(&mock_keys).expect("Failed to serialize mock keys");
+    // Tag the payload with a protocol version this build doesn't support,
+    // simulating a leader that has rolled forward to a newer key-sync
+    // schema than this worker understands.
+    let mut mock_keys_bytes = u32::MAX.to_le_bytes().to_vec();
+    mock_keys_bytes.extend_from_slice(&mock_keys_serialized);
+
+    let app = crate::app(&config, oprf_state.clone());
+
+    let request = test_request(
+        "/enclave/state",
+        Some(mock_keys_bytes.into()),
+        Some(Method::PUT),
+    );
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(oprf_state
+        .instances
+        .get("main")
+        .unwrap()
+        .read()
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_enclave_key_sync_over_secure_channel() {
+    // Both ends share a PSK, so they derive the same static channel
+    // identity and trust each other (`TrustPolicy::SharedSecret`).
+    let shared_secret = Some("correct horse battery staple".to_string());
+    let worker_handshake_port: u16 = 8094;
+
+    let leader_config = crate::Config {
+        listen: "127.0.0.1:8095".to_string(),
+        epoch_durations: vec!["1s".into()],
+        first_epoch: EPOCH,
+        last_epoch: EPOCH + 2,
+        epoch_base_time: None,
+        increase_nofile_limit: false,
+        prometheus_listen: None,
+        instance_names: vec!["main".to_string()],
+        enclave_key_sync: true,
+        nitriding_internal_port: Some(worker_handshake_port),
+        channel_psk: shared_secret.clone(),
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
+    };
+    let worker_config = crate::Config {
+        listen: format!("127.0.0.1:{worker_handshake_port}"),
+        channel_psk: shared_secret,
+        ..leader_config.clone()
+    };
+
+    // Run the worker as a real HTTP server, since the leader reaches it
+    // the same way it would reach nitriding's relay in production: a
+    // plain HTTP POST/PUT to a fixed local port.
+    let worker_state = OPRFServer::new(worker_config.clone()).await;
+    let worker_app = crate::app(&worker_config, worker_state.clone());
+    let worker_handle = tokio::spawn(async move {
+        let listener = TcpListener::bind(format!("127.0.0.1:{worker_handshake_port}"))
+            .await
+            .unwrap();
+        axum::serve(listener, worker_app).await.unwrap();
+    });
+
+    let leader_state = OPRFServer::new(leader_config.clone()).await;
+    let leader_app = crate::app(&leader_config, leader_state.clone());
+
+    // GET /enclave/state: becomes leader, performs the real handshake
+    // against the worker's /enclave/handshake over HTTP, then returns
+    // the key payload sealed under the resulting channel.
+    let request = test_request("/enclave/state", None, None);
+    let response = leader_app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(leader_state.is_leader.get(), Some(&true));
+    let sealed_body = to_bytes(response.into_body(), RESPONSE_MAX).await.unwrap();
+
+    // A bare `KeySyncEnvelope` can't be decoded without first opening
+    // the channel: confirms the body really is ciphertext, not the
+    // legacy plaintext format.
+    assert!(KeySyncEnvelope::decode(&sealed_body).is_err());
+
+    // Relay the sealed payload to the worker exactly as nitriding would.
+    crate::util::send_private_keys_to_nitriding(worker_handshake_port, sealed_body.to_vec())
+        .await
+        .expect("worker should accept the channel-sealed key payload");
+
+    let instance = worker_state.instances.get("main").unwrap();
+    let instance_guard = instance.read().await;
+    let worker_instance = instance_guard.as_ref().expect("worker should have set its key");
+
+    let leader_instance_guard = leader_state.instances.get("main").unwrap().read().await;
+    let leader_instance = leader_instance_guard.as_ref().unwrap();
+    assert_eq!(worker_instance.epoch, leader_instance.epoch);
+    assert_eq!(
+        worker_instance.server.get_private_key(),
+        leader_instance.server.get_private_key()
+    );
+    assert_eq!(worker_state.is_leader.get(), Some(&false));
+
+    worker_handle.abort();
+    worker_handle.await.ok();
+}
+
+#[tokio::test]
+async fn test_shutdown_flushes_keys_to_nitriding() {
+    let config = crate::Config {
+        listen: "127.0.0.1:8091".to_string(),
+        // Long enough that the natural epoch rotation won't fire during
+        // the test; the push we observe must be the shutdown flush.
+        epoch_durations: vec!["60s".into()],
+        first_epoch: EPOCH,
+        last_epoch: EPOCH + 2,
+        epoch_base_time: None,
+        increase_nofile_limit: false,
+        prometheus_listen: None,
+        instance_names: vec!["main".to_string()],
+        enclave_key_sync: true,
+        nitriding_internal_port: Some(8092),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
+    };
+
+    let (mock_server_handle, mut body_rx) = start_mock_nitriding_server(8092).await;
+
+    let oprf_state = OPRFServer::new(config.clone()).await;
+    let app = crate::app(&config, oprf_state.clone());
+
+    // Becomes the leader and creates its instances.
+    let request = test_request("/enclave/state", None, None);
+    app.oneshot(request).await.unwrap();
+    assert!(body_rx.is_empty());
+
+    oprf_state.shutdown().await;
+
+    let flushed_body = body_rx.recv().await.unwrap();
+    let flushed_keys = KeySyncEnvelope::decode(&flushed_body)
+        .unwrap()
+        .decode_keys()
+        .unwrap();
+    assert_eq!(flushed_keys.len(), 1);
+
+    mock_server_handle.abort();
+    mock_server_handle.await.ok();
+}
+
 #[tokio::test]
 async fn test_leader_updates_keys_with_nitriding() {
     let config = crate::Config {
@@ -559,6 +1087,18 @@ async fn test_leader_updates_keys_with_nitriding() {
         instance_names: vec!["main".to_string()],
         enclave_key_sync: true,
         nitriding_internal_port: Some(8087),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
     };
 
     let (mock_server_handle, mut body_rx) = start_mock_nitriding_server(8087).await;
@@ -575,7 +1115,10 @@ async fn test_leader_updates_keys_with_nitriding() {
     sleep(Duration::from_secs(1)).await;
 
     let updated_body = body_rx.recv().await.unwrap();
-    let updated_keys: OPRFKeys = bincode::deserialize(&updated_body).unwrap();
+    let updated_keys = KeySyncEnvelope::decode(&updated_body)
+        .unwrap()
+        .decode_keys()
+        .unwrap();
 
     assert_eq!(updated_keys.len(), 1);
 
@@ -595,6 +1138,110 @@ async fn test_leader_updates_keys_with_nitriding() {
     mock_server_handle.await.ok();
 }
 
+#[tokio::test]
+async fn test_leader_retries_nitriding_push_after_transient_failure() {
+    let config = crate::Config {
+        listen: "127.0.0.1:8089".to_string(),
+        epoch_durations: vec!["1s".into()],
+        first_epoch: EPOCH,
+        last_epoch: EPOCH + 2,
+        epoch_base_time: None,
+        increase_nofile_limit: false,
+        prometheus_listen: None,
+        instance_names: vec!["main".to_string()],
+        enclave_key_sync: true,
+        nitriding_internal_port: Some(8090),
+        channel_psk: None,
+        channel_trusted_peers: vec![],
+        channel_rekey_messages: 10_000,
+        channel_rekey_interval: "1h".into(),
+        worker_threads: None,
+        eval_cache_size: 0,
+        shutdown_grace: "30s".into(),
+        request_timeout: "30s".into(),
+        max_request_body_bytes: 1_048_576,
+        tcp_keepalive: true,
+        tcp_keepalive_time: "60s".into(),
+        tcp_nodelay: true,
+    };
+
+    // Fail the first two PUTs to simulate a relay hiccup, then accept.
+    let (mock_server_handle, mut body_rx, flaky_state) =
+        start_flaky_mock_nitriding_server(8090, 2).await;
+
+    let oprf_state = OPRFServer::new(config.clone()).await;
+    let app = crate::app(&config, oprf_state.clone());
+
+    let request = test_request("/enclave/state", None, None);
+    app.oneshot(request).await.unwrap();
+
+    // The epoch rotation triggers the first push attempt, which fails
+    // twice before the supervisor's backoff lets a retry succeed.
+    let updated_body = body_rx.recv().await.unwrap();
+    let updated_keys = KeySyncEnvelope::decode(&updated_body)
+        .unwrap()
+        .decode_keys()
+        .unwrap();
+    assert_eq!(updated_keys.len(), 1);
+    assert!(flaky_state.attempts.load(std::sync::atomic::Ordering::SeqCst) >= 3);
+
+    mock_server_handle.abort();
+    mock_server_handle.await.ok();
+}
+
+/// Mock nitriding server that rejects the first `fail_until` PUTs with a
+/// 503, then accepts and forwards every subsequent one. Used to exercise
+/// the leader's retry-with-backoff behavior against transient outages.
+struct FlakyNitridingState {
+    attempts: std::sync::atomic::AtomicUsize,
+    fail_until: usize,
+    body_tx: mpsc::UnboundedSender<Bytes>,
+}
+
+async fn start_flaky_mock_nitriding_server(
+    port: u16,
+    fail_until: usize,
+) -> (
+    JoinHandle<()>,
+    mpsc::UnboundedReceiver<Bytes>,
+    std::sync::Arc<FlakyNitridingState>,
+) {
+    let (body_tx, body_rx) = mpsc::unbounded_channel();
+    let state = std::sync::Arc::new(FlakyNitridingState {
+        attempts: std::sync::atomic::AtomicUsize::new(0),
+        fail_until,
+        body_tx,
+    });
+
+    let app = Router::new()
+        .route("/enclave/state", put(flaky_nitriding_put_state_handler))
+        .with_state(state.clone());
+
+    let handle = tokio::spawn(async move {
+        let listener = TcpListener::bind(format!("127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (handle, body_rx, state)
+}
+
+async fn flaky_nitriding_put_state_handler(
+    State(state): State<std::sync::Arc<FlakyNitridingState>>,
+    body: Bytes,
+) -> StatusCode {
+    let attempt = state
+        .attempts
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        + 1;
+    if attempt <= state.fail_until {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    state.body_tx.send(body).unwrap();
+    StatusCode::OK
+}
+
 async fn start_mock_nitriding_server(
     port: u16,
 ) -> (JoinHandle<()>, mpsc::UnboundedReceiver<Bytes>) {
@@ -0,0 +1,205 @@
+//! Append-only Merkle transparency log of per-epoch public keys.
+//!
+//! Lets a client audit that the public key it evaluated a point against
+//! was actually committed to the log the enclave publishes, rather than
+//! a value swapped in just for that client. A leaf `H(epoch || pubkey)`
+//! is appended every time an epoch advances or its key rotates; leaves
+//! are never mutated or removed, only appended.
+
+use sha2::{Digest, Sha256};
+
+fn leaf_hash(epoch: u8, public_key: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([epoch]);
+    hasher.update(public_key);
+    hasher.finalize().into()
+}
+
+fn parent_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// One sibling hash on the path from a leaf to the root, plus which side
+/// of the pair the accumulated hash was on at that level.
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    pub leaf_is_left: bool,
+}
+
+/// An inclusion proof for one committed leaf: its index in the log, the
+/// leaf hash itself, and the sibling hashes from the leaf's level up to
+/// (but not including) the root.
+pub struct InclusionProof {
+    pub leaf_index: usize,
+    pub leaf_hash: [u8; 32],
+    pub steps: Vec<ProofStep>,
+}
+
+impl InclusionProof {
+    /// Recompute the root implied by this proof and compare it against
+    /// `expected_root` — the same check an auditing client performs
+    /// independently after fetching both the proof and the root.
+    pub fn verify(&self, expected_root: [u8; 32]) -> bool {
+        let mut acc = self.leaf_hash;
+        for step in &self.steps {
+            acc = if step.leaf_is_left {
+                parent_hash(&acc, &step.sibling)
+            } else {
+                parent_hash(&step.sibling, &acc)
+            };
+        }
+        acc == expected_root
+    }
+}
+
+/// Append-only Merkle tree of per-epoch public-key commitments. Stored
+/// as flat leaves; every level above is recomputed from scratch on
+/// demand, which keeps appends and the invariant that leaves never
+/// change trivially easy to get right at the log sizes this enclave
+/// ever reaches.
+#[derive(Default)]
+pub struct TransparencyLog {
+    leaves: Vec<[u8; 32]>,
+}
+
+impl TransparencyLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a new `(epoch, public_key)` commitment.
+    pub fn append(&mut self, epoch: u8, public_key: &[u8]) {
+        self.leaves.push(leaf_hash(epoch, public_key));
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Build every level of the tree from the current leaves up, hashing
+    /// pairs left-to-right and duplicating the last node when a level
+    /// has odd length. `None` if nothing has been appended yet.
+    fn levels(&self) -> Option<Vec<Vec<[u8; 32]>>> {
+        if self.leaves.is_empty() {
+            return None;
+        }
+        let mut levels = vec![self.leaves.clone()];
+        while levels.last().unwrap().len() > 1 {
+            let current = levels.last().unwrap();
+            let mut next = Vec::with_capacity((current.len() + 1) / 2);
+            for pair in current.chunks(2) {
+                let parent = match pair {
+                    [left, right] => parent_hash(left, right),
+                    [only] => parent_hash(only, only),
+                    _ => unreachable!("chunks(2) never yields more than 2 elements"),
+                };
+                next.push(parent);
+            }
+            levels.push(next);
+        }
+        Some(levels)
+    }
+
+    /// The current root, or `None` if the log is empty.
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.levels().map(|levels| levels.last().unwrap()[0])
+    }
+
+    /// Build an inclusion proof for `(epoch, public_key)`'s most recent
+    /// commitment, searching from the end so a reused epoch tag finds
+    /// its latest leaf. `None` if that pair was never committed.
+    pub fn prove(&self, epoch: u8, public_key: &[u8]) -> Option<InclusionProof> {
+        let target = leaf_hash(epoch, public_key);
+        let leaf_index = self.leaves.iter().rposition(|leaf| *leaf == target)?;
+        let levels = self.levels()?;
+
+        let mut steps = Vec::with_capacity(levels.len().saturating_sub(1));
+        let mut index = leaf_index;
+        for level in &levels[..levels.len() - 1] {
+            let leaf_is_left = index % 2 == 0;
+            let sibling_index = if leaf_is_left {
+                (index + 1).min(level.len() - 1)
+            } else {
+                index - 1
+            };
+            steps.push(ProofStep {
+                sibling: level[sibling_index],
+                leaf_is_left,
+            });
+            index /= 2;
+        }
+
+        Some(InclusionProof {
+            leaf_index,
+            leaf_hash: target,
+            steps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_log_has_no_root() {
+        let log = TransparencyLog::new();
+        assert!(log.root().is_none());
+    }
+
+    #[test]
+    fn single_leaf_root_is_the_leaf_hash() {
+        let mut log = TransparencyLog::new();
+        log.append(1, b"pubkey-a");
+        assert_eq!(log.root(), Some(leaf_hash(1, b"pubkey-a")));
+    }
+
+    #[test]
+    fn proof_verifies_against_the_published_root_for_every_leaf() {
+        let mut log = TransparencyLog::new();
+        for (epoch, key) in [
+            (1u8, b"pubkey-a".as_slice()),
+            (2, b"pubkey-b"),
+            (3, b"pubkey-c"),
+            (4, b"pubkey-d"),
+            (5, b"pubkey-e"),
+        ] {
+            log.append(epoch, key);
+        }
+        let root = log.root().unwrap();
+
+        for (epoch, key) in [
+            (1u8, b"pubkey-a".as_slice()),
+            (2, b"pubkey-b"),
+            (3, b"pubkey-c"),
+            (4, b"pubkey-d"),
+            (5, b"pubkey-e"),
+        ] {
+            let proof = log.prove(epoch, key).expect("leaf was committed");
+            assert!(proof.verify(root));
+        }
+    }
+
+    #[test]
+    fn proof_rejects_an_uncommitted_leaf() {
+        let mut log = TransparencyLog::new();
+        log.append(1, b"pubkey-a");
+        assert!(log.prove(2, b"pubkey-a").is_none());
+    }
+
+    #[test]
+    fn tampered_root_fails_verification() {
+        let mut log = TransparencyLog::new();
+        log.append(1, b"pubkey-a");
+        log.append(2, b"pubkey-b");
+        let proof = log.prove(1, b"pubkey-a").unwrap();
+        assert!(!proof.verify([0xAA; 32]));
+    }
+}
@@ -3,8 +3,11 @@ use std::collections::HashSet;
 use reqwest::{Client, Method};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
+use crate::result::Error;
+use crate::state::channel;
+
 /// Parse a timestamp given as a config option
-pub fn parse_timestamp(stamp: &str) -> Result<OffsetDateTime, &'static str> {
+pub fn parse_timestamp(stamp: &str) -> std::result::Result<OffsetDateTime, &'static str> {
     OffsetDateTime::parse(stamp, &Rfc3339).map_err(|_| "Try something like '2023-05-15T04:30:00Z'.")
 }
 
@@ -25,7 +28,7 @@ pub fn format_rfc3339(date: &OffsetDateTime) -> String {
 pub async fn send_private_keys_to_nitriding(
     nitriding_internal_port: u16,
     private_key_bincode: Vec<u8>,
-) -> Result<(), reqwest::Error> {
+) -> std::result::Result<(), reqwest::Error> {
     let client = Client::new();
     let request = client
         .request(
@@ -34,5 +37,36 @@ pub async fn send_private_keys_to_nitriding(
         )
         .body(private_key_bincode)
         .build()?;
-    client.execute(request).await.map(|_| ())
+    client
+        .execute(request)
+        .await?
+        .error_for_status()
+        .map(|_| ())
+}
+
+/// Run the initiator side of the enclave key-sync handshake over
+/// nitriding's internal port, which is expected to relay the POST the
+/// same way it already relays `/enclave/state` to the worker enclave(s).
+pub async fn perform_handshake_over_nitriding(
+    nitriding_internal_port: u16,
+    identity: &channel::ChannelIdentity,
+    rekey: channel::RekeyPolicy,
+) -> crate::result::Result<channel::SecureChannel> {
+    let (initiator_state, init) = channel::handshake_initiator_start(identity, rekey);
+
+    let client = Client::new();
+    let response = client
+        .post(format!(
+            "http://127.0.0.1:{nitriding_internal_port}/enclave/handshake"
+        ))
+        .body(init.to_bytes().to_vec())
+        .send()
+        .await
+        .map_err(|_| Error::ChannelCryptoFailure)?
+        .bytes()
+        .await
+        .map_err(|_| Error::ChannelCryptoFailure)?;
+
+    let resp = channel::HandshakeResponse::from_bytes(&response)?;
+    channel::handshake_initiator_finish(initiator_state, resp)
 }